@@ -50,6 +50,10 @@ pub fn ax_flush_file(file: &AxFileHandle) -> AxResult {
     file.0.flush()
 }
 
+pub fn ax_sync_file(file: &AxFileHandle) -> AxResult {
+    file.0.sync()
+}
+
 pub fn ax_seek_file(file: &mut AxFileHandle, pos: AxSeekFrom) -> AxResult<u64> {
     file.0.seek(pos)
 }
@@ -85,3 +89,7 @@ pub fn ax_current_dir() -> AxResult<String> {
 pub fn ax_set_current_dir(path: &str) -> AxResult {
     axfs::api::set_current_dir(path)
 }
+
+pub fn ax_canonicalize(path: &str) -> AxResult<String> {
+    axfs::api::canonicalize(path)
+}
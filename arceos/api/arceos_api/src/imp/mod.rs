@@ -45,4 +45,6 @@ pub use self::task::*;
 pub use self::time::*;
 
 pub use axhal::misc::terminate as ax_terminate;
+pub use axhal::misc::random as ax_random;
+pub use axhal::misc::terminate_with_code as ax_terminate_with_code;
 pub use axio::PollState as AxPollState;
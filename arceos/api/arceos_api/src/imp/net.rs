@@ -60,10 +60,26 @@ pub fn ax_tcp_poll(socket: &AxTcpSocketHandle) -> AxResult<AxPollState> {
     socket.0.poll()
 }
 
+pub fn ax_tcp_ttl(socket: &AxTcpSocketHandle) -> AxResult<u32> {
+    socket.0.ttl()
+}
+
+pub fn ax_tcp_set_ttl(socket: &AxTcpSocketHandle, ttl: u32) -> AxResult {
+    socket.0.set_ttl(ttl)
+}
+
 pub fn ax_tcp_shutdown(socket: &AxTcpSocketHandle) -> AxResult {
     socket.0.shutdown()
 }
 
+pub fn ax_tcp_shutdown_read(socket: &AxTcpSocketHandle) -> AxResult {
+    socket.0.shutdown_read()
+}
+
+pub fn ax_tcp_shutdown_write(socket: &AxTcpSocketHandle) -> AxResult {
+    socket.0.shutdown_write()
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // UDP socket
 ////////////////////////////////////////////////////////////////////////////////
@@ -85,6 +101,23 @@ pub fn ax_udp_set_nonblocking(socket: &AxUdpSocketHandle, nonblocking: bool) ->
     Ok(())
 }
 
+pub fn ax_udp_broadcast(socket: &AxUdpSocketHandle) -> AxResult<bool> {
+    Ok(socket.0.is_broadcast())
+}
+
+pub fn ax_udp_set_broadcast(socket: &AxUdpSocketHandle, broadcast: bool) -> AxResult {
+    socket.0.set_broadcast(broadcast);
+    Ok(())
+}
+
+pub fn ax_udp_ttl(socket: &AxUdpSocketHandle) -> AxResult<u32> {
+    Ok(socket.0.ttl())
+}
+
+pub fn ax_udp_set_ttl(socket: &AxUdpSocketHandle, ttl: u32) -> AxResult {
+    socket.0.set_ttl(ttl)
+}
+
 pub fn ax_udp_bind(socket: &AxUdpSocketHandle, addr: SocketAddr) -> AxResult {
     socket.0.bind(addr)
 }
@@ -56,6 +56,10 @@ cfg_task! {
         axtask::current().id().as_u64()
     }
 
+    pub fn ax_current_task_name() -> alloc::string::String {
+        axtask::current().name().into()
+    }
+
     pub fn ax_spawn<F>(f: F, name: alloc::string::String, stack_size: usize) -> AxTaskHandle
     where
         F: FnOnce() + Send + 'static,
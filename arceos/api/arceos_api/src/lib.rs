@@ -32,6 +32,12 @@ pub mod sys {
     define_api! {
         /// Shutdown the whole system and all CPUs.
         pub fn ax_terminate() -> !;
+        /// Shutdown the whole system and all CPUs, reporting the exit code to
+        /// the host if the platform supports it.
+        pub fn ax_terminate_with_code(exit_code: i32) -> !;
+        /// Returns a pseudo-random number from the platform, e.g. to seed
+        /// hashers.
+        pub fn ax_random() -> u128;
     }
 }
 
@@ -144,6 +150,8 @@ pub mod task {
 
         /// Returns the current task's ID.
         pub fn ax_current_task_id() -> u64;
+        /// Returns the current task's name.
+        pub fn ax_current_task_name() -> alloc::string::String;
         /// Spawns a new task with the given entry point and other arguments.
         pub fn ax_spawn(
             f: impl FnOnce() + Send + 'static,
@@ -225,6 +233,9 @@ pub mod fs {
         pub fn ax_truncate_file(file: &AxFileHandle, size: u64) -> AxResult;
         /// Flushes the file, writes all buffered data to the underlying device.
         pub fn ax_flush_file(file: &AxFileHandle) -> AxResult;
+        /// Synchronizes the file data and metadata to the underlying device,
+        /// whether the file is writable or not.
+        pub fn ax_sync_file(file: &AxFileHandle) -> AxResult;
         /// Sets the cursor of the file to the specified offset. Returns the new
         /// position after the seek.
         pub fn ax_seek_file(file: &mut AxFileHandle, pos: AxSeekFrom) -> AxResult<u64>;
@@ -254,6 +265,9 @@ pub mod fs {
         pub fn ax_current_dir() -> AxResult<alloc::string::String>;
         /// Changes the current working directory to the specified path.
         pub fn ax_set_current_dir(path: &str) -> AxResult;
+        /// Returns the canonical, absolute form of a path, with all `.` and
+        /// `..` components resolved. The path must exist.
+        pub fn ax_canonicalize(path: &str) -> AxResult<alloc::string::String>;
     }
 }
 
@@ -301,8 +315,18 @@ pub mod net {
         pub fn ax_tcp_recv(socket: &AxTcpSocketHandle, buf: &mut [u8]) -> AxResult<usize>;
         /// Returns whether the TCP socket is readable or writable.
         pub fn ax_tcp_poll(socket: &AxTcpSocketHandle) -> AxResult<AxPollState>;
+        /// Returns the time-to-live of the IP packets sent on the TCP socket.
+        pub fn ax_tcp_ttl(socket: &AxTcpSocketHandle) -> AxResult<u32>;
+        /// Sets the time-to-live of the IP packets sent on the TCP socket.
+        pub fn ax_tcp_set_ttl(socket: &AxTcpSocketHandle, ttl: u32) -> AxResult;
         /// Closes the connection on the TCP socket.
         pub fn ax_tcp_shutdown(socket: &AxTcpSocketHandle) -> AxResult;
+        /// Shuts down the read half of the connection on the TCP socket, later
+        /// receives return 0.
+        pub fn ax_tcp_shutdown_read(socket: &AxTcpSocketHandle) -> AxResult;
+        /// Shuts down the write half of the connection on the TCP socket, the
+        /// peer reads EOF after the data sent before.
+        pub fn ax_tcp_shutdown_write(socket: &AxTcpSocketHandle) -> AxResult;
 
         // UDP socket
 
@@ -314,6 +338,14 @@ pub mod net {
         pub fn ax_udp_peer_addr(socket: &AxUdpSocketHandle) -> AxResult<SocketAddr>;
         /// Moves this UDP socket into or out of nonblocking mode.
         pub fn ax_udp_set_nonblocking(socket: &AxUdpSocketHandle, nonblocking: bool) -> AxResult;
+        /// Returns whether the UDP socket may send to broadcast addresses.
+        pub fn ax_udp_broadcast(socket: &AxUdpSocketHandle) -> AxResult<bool>;
+        /// Allows or forbids the UDP socket to send to broadcast addresses.
+        pub fn ax_udp_set_broadcast(socket: &AxUdpSocketHandle, broadcast: bool) -> AxResult;
+        /// Returns the time-to-live of the IP packets sent on the UDP socket.
+        pub fn ax_udp_ttl(socket: &AxUdpSocketHandle) -> AxResult<u32>;
+        /// Sets the time-to-live of the IP packets sent on the UDP socket.
+        pub fn ax_udp_set_ttl(socket: &AxUdpSocketHandle, ttl: u32) -> AxResult;
 
         /// Binds the UDP socket to the given address and port.
         pub fn ax_udp_bind(socket: &AxUdpSocketHandle, addr: SocketAddr) -> AxResult;
@@ -0,0 +1,21 @@
+#![cfg_attr(feature = "axstd", no_std)]
+#![cfg_attr(feature = "axstd", no_main)]
+
+#[cfg(feature = "axstd")]
+use axstd::{env, println, process};
+#[cfg(not(feature = "axstd"))]
+use std::{env, process};
+
+/// Exits with the code given as the first argument (`ARGS=<code>`), or aborts
+/// with `ARGS=abort`, for `test_exit_code.sh` to check the exit status of QEMU.
+#[cfg_attr(feature = "axstd", no_mangle)]
+fn main() {
+    let arg = env::args().nth(1).unwrap_or_default();
+    if arg == "abort" {
+        println!("Aborting");
+        process::abort();
+    }
+    let code: i32 = arg.parse().expect("the exit code must be an integer");
+    println!("Exiting with code {}", code);
+    process::exit(code);
+}
@@ -0,0 +1,63 @@
+//! Configuration of the guest.
+
+/// Where the guest is loaded and started, and its physical memory.
+#[derive(Clone, Copy, Debug)]
+pub struct GuestConfig {
+    /// Entry point of a raw guest image, which is loaded there. ELF images
+    /// start at their own entry point instead.
+    pub entry: usize,
+    /// Guest physical address of the guest RAM.
+    pub ram_base: usize,
+    /// Size in bytes of the guest RAM.
+    pub ram_size: usize,
+}
+
+impl GuestConfig {
+    /// Returns the default layout, changed by the `key=value` settings in
+    /// `args`: `entry`, `ram_base` or `ram_size`, with a decimal or
+    /// `0x`-prefixed hexadecimal value, e.g. `entry=0x80400000`.
+    ///
+    /// Returns the first argument that is not such a setting as the error.
+    pub fn from_args<S: AsRef<str>>(args: impl IntoIterator<Item = S>) -> Result<Self, S> {
+        let mut config = Self::default();
+        for arg in args {
+            let Some((key, value)) = arg.as_ref().split_once('=') else {
+                return Err(arg);
+            };
+            let Some(value) = parse_usize(value) else {
+                return Err(arg);
+            };
+            match key {
+                "entry" => config.entry = value,
+                "ram_base" => config.ram_base = value,
+                "ram_size" => config.ram_size = value,
+                _ => return Err(arg),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Returns `true` if the guest physical address `gpa` is in guest RAM.
+    pub fn in_ram(&self, gpa: usize) -> bool {
+        (self.ram_base..self.ram_base + self.ram_size).contains(&gpa)
+    }
+}
+
+impl Default for GuestConfig {
+    /// The layout of the QEMU `virt` machine, with the guest kernel where
+    /// OpenSBI would jump to.
+    fn default() -> Self {
+        Self {
+            entry: 0x8020_0000,
+            ram_base: 0x8000_0000,
+            ram_size: 0x800_0000,
+        }
+    }
+}
+
+fn parse_usize(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
@@ -12,6 +12,17 @@ pub struct CSR {
     pub hideleg: ReadWriteCsr<hideleg::Register, CSR_HIDELEG>,
     pub hcounteren: ReadWriteCsr<hcounteren::Register, CSR_HCOUNTEREN>,
     pub hvip: ReadWriteCsr<hvip::Register, CSR_HVIP>,
+    pub htval: ReadWriteCsr<(), CSR_HTVAL>,
+    pub htinst: ReadWriteCsr<(), CSR_HTINST>,
+    pub htimedelta: ReadWriteCsr<(), CSR_HTIMEDELTA>,
+    pub vsstatus: ReadWriteCsr<(), CSR_VSSTATUS>,
+    pub vsie: ReadWriteCsr<(), CSR_VSIE>,
+    pub vstvec: ReadWriteCsr<(), CSR_VSTVEC>,
+    pub vsscratch: ReadWriteCsr<(), CSR_VSSCRATCH>,
+    pub vsepc: ReadWriteCsr<(), CSR_VSEPC>,
+    pub vscause: ReadWriteCsr<(), CSR_VSCAUSE>,
+    pub vstval: ReadWriteCsr<(), CSR_VSTVAL>,
+    pub vsatp: ReadWriteCsr<(), CSR_VSATP>,
 }
 
 #[allow(clippy::identity_op, clippy::erasing_op)]
@@ -22,6 +33,17 @@ pub const CSR: &CSR = &CSR {
     hideleg: ReadWriteCsr::new(),
     hcounteren: ReadWriteCsr::new(),
     hvip: ReadWriteCsr::new(),
+    htval: ReadWriteCsr::new(),
+    htinst: ReadWriteCsr::new(),
+    htimedelta: ReadWriteCsr::new(),
+    vsstatus: ReadWriteCsr::new(),
+    vsie: ReadWriteCsr::new(),
+    vstvec: ReadWriteCsr::new(),
+    vsscratch: ReadWriteCsr::new(),
+    vsepc: ReadWriteCsr::new(),
+    vscause: ReadWriteCsr::new(),
+    vstval: ReadWriteCsr::new(),
+    vsatp: ReadWriteCsr::new(),
 };
 
 /// Trait defining the possible operations on a RISC-V CSR.
@@ -169,6 +191,12 @@ pub mod defs {
     pub const CSR_HGATP: u16 = 0x680;
     pub const CSR_HCONTEXT: u16 = 0x6a8;
     pub const CSR_HGEIP: u16 = 0xe12;
+    // Machine-level ID CSRs, which the guest may probe.
+    pub const CSR_MISA: u16 = 0x301;
+    pub const CSR_MVENDORID: u16 = 0xf11;
+    pub const CSR_MARCHID: u16 = 0xf12;
+    pub const CSR_MIMPID: u16 = 0xf13;
+    pub const CSR_MHARTID: u16 = 0xf14;
 
     // Hypervisor exception delegation register.
     register_bitfields![usize,
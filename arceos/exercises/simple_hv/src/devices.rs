@@ -0,0 +1,86 @@
+//! Emulated MMIO devices of the guest.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A device emulated by trapping guest accesses to its MMIO region.
+pub trait MmioDevice {
+    /// The guest physical address range of the device registers.
+    fn mmio_range(&self) -> Range<usize>;
+
+    /// Handles a guest read of `width` bytes at `offset` in the region.
+    fn read(&mut self, offset: usize, width: usize) -> usize;
+
+    /// Handles a guest write of `width` bytes at `offset` in the region.
+    fn write(&mut self, offset: usize, width: usize, val: usize);
+}
+
+/// All the emulated MMIO devices of a guest.
+#[derive(Default)]
+pub struct MmioBus {
+    devices: Vec<Box<dyn MmioDevice>>,
+}
+
+impl MmioBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a device to the bus.
+    pub fn register(&mut self, dev: Box<dyn MmioDevice>) {
+        self.devices.push(dev);
+    }
+
+    /// Finds the device that covers the guest physical address `gpa`, and
+    /// returns it along with the offset of `gpa` in its region.
+    pub fn find(&mut self, gpa: usize) -> Option<(&mut dyn MmioDevice, usize)> {
+        self.devices.iter_mut().find_map(|dev| {
+            let range = dev.mmio_range();
+            if range.contains(&gpa) {
+                Some((dev.as_mut(), gpa - range.start))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Guest physical address of the UART on the QEMU `virt` machine.
+pub const VIRT_UART_BASE: usize = 0x1000_0000;
+
+const UART_THR: usize = 0; // Transmitter holding register
+const UART_LSR: usize = 5; // Line status register
+const LSR_THRE: usize = 1 << 5; // Transmitter holding register empty
+const LSR_TEMT: usize = 1 << 6; // Transmitter empty
+
+/// A write-only 16550-like UART, whose output goes to the host console.
+pub struct VirtConsole {
+    base: usize,
+}
+
+impl VirtConsole {
+    pub const fn new(base: usize) -> Self {
+        Self { base }
+    }
+}
+
+impl MmioDevice for VirtConsole {
+    fn mmio_range(&self) -> Range<usize> {
+        self.base..self.base + 0x1000
+    }
+
+    fn read(&mut self, offset: usize, _width: usize) -> usize {
+        match offset {
+            // always ready to transmit
+            UART_LSR => LSR_THRE | LSR_TEMT,
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, offset: usize, _width: usize, val: usize) {
+        if offset == UART_THR {
+            ax_print!("{}", val as u8 as char);
+        }
+    }
+}
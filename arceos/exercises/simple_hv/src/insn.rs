@@ -0,0 +1,45 @@
+//! Fetching of trapped guest instructions. They are decoded by the
+//! [`riscv_insn`] crate, which is unit tested on the host.
+
+use riscv_insn::{insn_len, transformed_insn};
+
+pub use riscv_insn::{decode_csr_read, decode_mem_access};
+
+/// Reads a 16-bit instruction parcel from guest memory with `hlvx.hu`, using
+/// the guest's own address translation.
+fn hlvx_hu(gva: usize) -> u16 {
+    let val: usize;
+    unsafe {
+        // hlvx.hu {val}, ({gva})
+        core::arch::asm!(
+            ".insn r 0x73, 0x4, 0x32, {val}, {gva}, x3",
+            val = out(reg) val,
+            gva = in(reg) gva,
+        );
+    }
+    val as u16
+}
+
+/// Fetches the guest instruction at `gva`.
+///
+/// The upper half is only read for 4-byte instructions, so fetching a
+/// compressed instruction at the end of a page does not touch the next one.
+pub fn fetch_guest_insn(gva: usize) -> u32 {
+    let low = hlvx_hu(gva) as u32;
+    if insn_len(low) == 2 {
+        low
+    } else {
+        low | (hlvx_hu(gva + 2) as u32) << 16
+    }
+}
+
+/// Returns the instruction that trapped into HS-mode, and its length.
+///
+/// Uses the transformed instruction in `htinst` if the hardware provides one,
+/// otherwise fetches the original instruction from guest memory at `sepc`.
+pub fn trapped_insn(htinst: usize, sepc: usize) -> (u32, usize) {
+    transformed_insn(htinst).unwrap_or_else(|| {
+        let insn = fetch_guest_insn(sepc);
+        (insn, insn_len(insn))
+    })
+}
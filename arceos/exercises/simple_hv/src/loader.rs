@@ -1,37 +1,142 @@
 use std::io::{self, Read};
+use std::io::SeekFrom;
+use std::io::Seek;
 use std::fs::File;
+use alloc::vec::Vec;
+use alloc::vec;
 use axhal::paging::MappingFlags;
-use axhal::mem::{PAGE_SIZE_4K, phys_to_virt};
+use axhal::mem::{PAGE_SIZE_4K, VirtAddr, MemoryAddr};
 use axmm::AddrSpace;
-use crate::VM_ENTRY;
+use crate::config::GuestConfig;
 
-pub fn load_vm_image(fname: &str, uspace: &mut AddrSpace) -> io::Result<()> {
-    let mut buf = [0u8; 64];
-    load_file(fname, &mut buf)?;
+use elf::abi::{ELFMAGIC, PF_R, PF_W, PF_X, PT_LOAD};
+use elf::endian::AnyEndian;
+use elf::parse::ParseAt;
+use elf::segment::ProgramHeader;
+use elf::segment::SegmentTable;
+use elf::ElfBytes;
 
-    uspace.map_alloc(VM_ENTRY.into(), PAGE_SIZE_4K, MappingFlags::READ|MappingFlags::WRITE|MappingFlags::EXECUTE|MappingFlags::USER, true).unwrap();
+const ELF_HEAD_BUF_SIZE: usize = 256;
 
-    let (paddr, _, _) = uspace
-        .page_table()
-        .query(VM_ENTRY.into())
-        .unwrap_or_else(|_| panic!("Mapping failed for segment: {:#x}", VM_ENTRY));
+/// Loads the guest image `fname` into the guest address space `uspace`, and
+/// returns its entry point.
+///
+/// ELF images have each `PT_LOAD` segment loaded at its physical address,
+/// which must be in the guest RAM of `config`. Anything else is taken as a raw
+/// binary and loaded whole at the entry point of `config`, which it must fit
+/// in guest RAM from.
+pub fn load_vm_image(fname: &str, uspace: &mut AddrSpace, config: &GuestConfig) -> io::Result<usize> {
+    ax_println!("app: {}", fname);
+    let mut file = File::open(fname)?;
+    let mut magic = [0u8; 4];
+    let n = file.read(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    if n == magic.len() && magic == ELFMAGIC {
+        load_elf_image(&mut file, uspace, config)
+    } else {
+        load_raw_image(&mut file, uspace, config)
+    }
+}
+
+fn load_raw_image(file: &mut File, uspace: &mut AddrSpace, config: &GuestConfig) -> io::Result<usize> {
+    let mut image = Vec::new();
+    file.read_to_end(&mut image)?;
 
-    ax_println!("paddr: {:#x}", paddr);
+    let entry = config.entry;
+    let start = VirtAddr::from(entry);
+    let end = VirtAddr::from(entry + image.len()).align_up_4k();
+    if image.is_empty()
+        || !start.is_aligned_4k()
+        || entry < config.ram_base
+        || end.as_usize() > config.ram_base + config.ram_size
+    {
+        return Err(io::Error::InvalidData);
+    }
+    let flags = MappingFlags::READ | MappingFlags::WRITE | MappingFlags::EXECUTE | MappingFlags::USER;
+    uspace.map_alloc(start, end - start, flags, true)?;
+    uspace.write(start, &image)?;
+    ax_println!("raw image: {:#x} bytes at {:#x}", image.len(), entry);
+
+    Ok(entry)
+}
 
-    unsafe {
-        core::ptr::copy_nonoverlapping(
-            buf.as_ptr(),
-            phys_to_virt(paddr).as_mut_ptr(),
-            PAGE_SIZE_4K,
+fn load_elf_image(file: &mut File, uspace: &mut AddrSpace, config: &GuestConfig) -> io::Result<usize> {
+    let (phdrs, entry) = load_elf_phdrs(file)?;
+
+    for phdr in &phdrs {
+        ax_println!(
+            "phdr: offset: {:#X}=>{:#X} size: {:#X}=>{:#X}",
+            phdr.p_offset, phdr.p_paddr, phdr.p_filesz, phdr.p_memsz
         );
+
+        // Guest physical addresses are the addresses of the guest space.
+        let gpa = VirtAddr::from(phdr.p_paddr as usize);
+        let start = gpa.align_down_4k();
+        let end = VirtAddr::from((phdr.p_paddr + phdr.p_memsz) as usize).align_up_4k();
+        if start.as_usize() < config.ram_base || end.as_usize() > config.ram_base + config.ram_size {
+            return Err(io::Error::InvalidData);
+        }
+        uspace.map_alloc(start, end - start, segment_flags(phdr.p_flags), true)?;
+
+        let mut data = vec![0u8; phdr.p_memsz as usize];
+        file.seek(SeekFrom::Start(phdr.p_offset))?;
+
+        let filesz = phdr.p_filesz as usize;
+        let mut index = 0;
+        while index < filesz {
+            let n = file.read(&mut data[index..filesz])?;
+            if n == 0 {
+                return Err(io::Error::UnexpectedEof);
+            }
+            index += n;
+        }
+        uspace.write(gpa, &data)?;
     }
 
-    Ok(())
+    Ok(entry)
 }
 
-fn load_file(fname: &str, buf: &mut [u8]) -> io::Result<usize> {
-    ax_println!("app: {}", fname);
-    let mut file = File::open(fname)?;
-    let n = file.read(buf)?;
-    Ok(n)
+/// Guest memory flags for an ELF segment with permissions `p_flags`.
+fn segment_flags(p_flags: u32) -> MappingFlags {
+    // All G-stage mappings must be user mappings.
+    let mut flags = MappingFlags::USER;
+    if p_flags & PF_R != 0 {
+        flags |= MappingFlags::READ;
+    }
+    if p_flags & PF_W != 0 {
+        flags |= MappingFlags::WRITE;
+    }
+    if p_flags & PF_X != 0 {
+        flags |= MappingFlags::EXECUTE;
+    }
+    flags
+}
+
+fn load_elf_phdrs(file: &mut File) -> io::Result<(Vec<ProgramHeader>, usize)> {
+    let mut buf: [u8; ELF_HEAD_BUF_SIZE] = [0; ELF_HEAD_BUF_SIZE];
+    file.read(&mut buf)?;
+
+    let ehdr = ElfBytes::<AnyEndian>::parse_elf_header(&buf[..])
+        .map_err(|_| io::Error::InvalidData)?;
+    info!("e_entry: {:#X}", ehdr.e_entry);
+
+    let phnum = ehdr.e_phnum as usize;
+    // Validate phentsize before trying to read the table so that we can error early for corrupted files
+    let entsize = ProgramHeader::validate_entsize(ehdr.class, ehdr.e_phentsize as usize)
+        .map_err(|_| io::Error::InvalidData)?;
+    let size = entsize.checked_mul(phnum).ok_or(io::Error::InvalidData)?;
+    if size == 0 || size > PAGE_SIZE_4K {
+        return Err(io::Error::InvalidData);
+    }
+    let mut buf = vec![0u8; size];
+    file.seek(SeekFrom::Start(ehdr.e_phoff))?;
+    file.read(&mut buf)?;
+    let phdrs = SegmentTable::new(ehdr.endianness, ehdr.class, &buf[..]);
+
+    let phdrs: Vec<ProgramHeader> = phdrs
+        .iter()
+        .filter(|phdr| phdr.p_type == PT_LOAD)
+        .collect();
+    Ok((phdrs, ehdr.e_entry as usize))
 }
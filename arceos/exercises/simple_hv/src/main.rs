@@ -10,50 +10,156 @@ extern crate alloc;
 extern crate axlog;
 
 mod task;
+mod config;
 mod vcpu;
 mod regs;
 mod csrs;
 mod sbi;
 mod loader;
+mod devices;
+mod insn;
 
-use vcpu::VmCpuRegisters;
-use riscv::register::{scause, sstatus, stval};
-use csrs::defs::hstatus;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+#[cfg(feature = "watchdog")]
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::{AtomicU64, Ordering};
+use vcpu::{HartState, VCpu, VmCpuRegisters, VmCpuTrapState, SSTATUS_SIE};
+use riscv::register::{scause, sstatus, stval, time};
+use csrs::defs::{hcounteren, hstatus, CSR_MARCHID, CSR_MHARTID, CSR_MIMPID, CSR_MISA, CSR_MVENDORID};
 use tock_registers::LocalRegisterCopy;
-use csrs::{RiscvCsrTrait, CSR};
-use vcpu::_run_guest;
-use sbi::SbiMessage;
+use tock_registers::interfaces::Writeable;
+use csrs::{traps, RiscvCsrTrait, CSR};
+use sbi::{DebugConsoleFunction, HsmFunction, ResetFunction, ResetReason, ResetType, SbiMessage, SbiReturn};
 use loader::load_vm_image;
-use axhal::mem::PhysAddr;
-use crate::regs::GprIndex::{A0, A1};
+use config::GuestConfig;
+use axhal::mem::{MemoryAddr, PhysAddr, VirtAddr, PAGE_SIZE_4K};
+use axhal::paging::MappingFlags;
+use axmm::AddrSpace;
+use crate::regs::GprIndex::{A0, A1, A6, A7};
+use devices::{MmioBus, VirtConsole, VIRT_UART_BASE};
 
-const VM_ENTRY: usize = 0x8020_0000;
+/// The `misa` the guest sees: RV64 with the I, M, A, F, D, C, S and U
+/// extensions.
+const GUEST_MISA: usize = 2 << 62
+    | 1 << (b'I' - b'A')
+    | 1 << (b'M' - b'A')
+    | 1 << (b'A' - b'A')
+    | 1 << (b'F' - b'A')
+    | 1 << (b'D' - b'A')
+    | 1 << (b'C' - b'A')
+    | 1 << (b'S' - b'A')
+    | 1 << (b'U' - b'A');
+/// Number of harts of the guest. Only hart 0 runs at boot, the others are
+/// started by the guest through the SBI HSM extension.
+const GUEST_HARTS: usize = 2;
+/// The guest image loaded when none is given as the first argument.
+const DEFAULT_GUEST_IMAGE: &str = "/sbin/skernel2";
+/// How long the guest runs before the host timer interrupts it, to let the
+/// host tasks run.
+const HOST_TIME_SLICE_NS: u64 = 10_000_000;
+
+/// End of the current time slice of the guest, in ticks of the `time` CSR.
+static SLICE_END: AtomicU64 = AtomicU64::new(0);
+
+/// Number of times the watchdog task ran while the guest was running.
+#[cfg(feature = "watchdog")]
+static WATCHDOG_TICKS: AtomicUsize = AtomicUsize::new(0);
 
 #[cfg_attr(feature = "axstd", no_mangle)]
 fn main() {
     ax_println!("Hypervisor ...");
+    // The guest layout can be changed by `key=value` settings after the image,
+    // e.g. `make ARGS="/sbin/skernel19 entry=0x80400000" run`.
+    let config = GuestConfig::from_args(std::env::args().skip(2))
+        .unwrap_or_else(|arg| panic!("Bad guest setting: {}", arg));
 
     // A new address space for vm.
     let mut uspace = axmm::new_user_aspace().unwrap();
 
-    // Load vm binary file into address space.
-    if let Err(e) = load_vm_image("/sbin/skernel2", &mut uspace) {
-        panic!("Cannot load app! {:?}", e);
-    }
+    // Load vm image file into address space. The image can be given as the
+    // first argument, e.g. `make ARGS=/sbin/skernel3 run`.
+    let image = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_GUEST_IMAGE.into());
+    let entry = match load_vm_image(&image, &mut uspace, &config) {
+        Ok(entry) => entry,
+        Err(e) => panic!("Cannot load app! {:?}", e),
+    };
 
     // Setup context to prepare to enter guest mode.
-    let mut ctx = VmCpuRegisters::default();
-    prepare_guest_context(&mut ctx);
+    let mut vcpus: Vec<VCpu> = (0..GUEST_HARTS).map(VCpu::new).collect();
+    prepare_guest_context(&mut vcpus[0], entry);
 
     // Setup pagetable for 2nd address mapping.
     let ept_root = uspace.page_table_root();
     prepare_vm_pgtable(ept_root);
 
-    // Kick off vm and wait for it to exit.
-    while !run_guest(&mut ctx) {
+    // Emulated devices of the guest.
+    let mut mmio = MmioBus::new();
+    mmio.register(Box::new(VirtConsole::new(VIRT_UART_BASE)));
+
+    #[cfg(feature = "watchdog")]
+    std::thread::spawn(watchdog);
+
+    // Kick off vm and wait for it to exit, restarting it on reboots.
+    let exit = loop {
+        match run_vcpus(&mut vcpus, &config, &mut uspace, &mut mmio) {
+            VmExit::Reboot => reboot_guest(&mut vcpus, entry),
+            exit => break exit,
+        }
+    };
+
+    match exit {
+        VmExit::Shutdown(ResetReason::NoReason) => {
+            #[cfg(feature = "watchdog")]
+            {
+                let ticks = WATCHDOG_TICKS.load(Ordering::Relaxed);
+                ax_println!("Watchdog ticks: {}", ticks);
+                assert!(ticks > 0, "the guest was never preempted");
+            }
+            ax_println!("Hypervisor ok!");
+            std::process::exit(0);
+        },
+        VmExit::Shutdown(ResetReason::SystemFailure) => {
+            ax_println!("Guest shut down on a system failure");
+            std::process::exit(1);
+        },
+        VmExit::AllHartsStopped => {
+            ax_println!("No guest hart is running");
+            std::process::exit(1);
+        },
+        VmExit::GuestFault { cause, sepc, stval } => {
+            ax_println!("Guest fault: {:?}, sepc: {:#x}, stval: {:#x}", cause, sepc, stval);
+            std::process::exit(1);
+        },
+        VmExit::Reboot => unreachable!(),
+    }
+}
+
+/// A host task counting how often it gets the CPU, to check that the guest is
+/// preempted and the host tasks still run.
+#[cfg(feature = "watchdog")]
+fn watchdog() {
+    loop {
+        WATCHDOG_TICKS.fetch_add(1, Ordering::Relaxed);
+        axtask::yield_now();
     }
+}
 
-    panic!("Hypervisor ok!");
+/// The reason the guest stopped running.
+#[derive(Debug)]
+enum VmExit {
+    /// The guest asked for a shutdown, for the given reason.
+    Shutdown(ResetReason),
+    /// The guest asked for a cold or warm reboot.
+    Reboot,
+    /// No hart of the guest is left running, so it can make no progress.
+    AllHartsStopped,
+    /// The guest hit a trap that cannot be handled for it.
+    GuestFault {
+        cause: scause::Trap,
+        sepc: usize,
+        stval: usize,
+    },
 }
 
 fn prepare_vm_pgtable(ept_root: PhysAddr) {
@@ -67,65 +173,507 @@ fn prepare_vm_pgtable(ept_root: PhysAddr) {
     }
 }
 
-fn run_guest(ctx: &mut VmCpuRegisters) -> bool {
-    unsafe {
-        _run_guest(ctx);
+/// Runs the started vCPUs in turn, one VM exit at a time, until the guest
+/// stops, or until none of them is started.
+fn run_vcpus(
+    vcpus: &mut [VCpu],
+    config: &GuestConfig,
+    aspace: &mut AddrSpace,
+    mmio: &mut MmioBus,
+) -> VmExit {
+    let mut next = 0;
+    loop {
+        if !vcpus.iter().any(|v| v.state == HartState::Started) {
+            return VmExit::AllHartsStopped;
+        }
+        let idx = next;
+        next = (next + 1) % vcpus.len();
+        if vcpus[idx].state != HartState::Started {
+            continue;
+        }
+        // Take the vCPU out, so that its exit handler can reach the others.
+        let mut vcpu = core::mem::take(&mut vcpus[idx]);
+        let exit = run_guest(&mut vcpu, vcpus, config, aspace, mmio);
+        vcpus[idx] = vcpu;
+        if let Some(exit) = exit {
+            return exit;
+        }
     }
-
-    vmexit_handler(ctx)
 }
 
-#[allow(unreachable_code)]
-fn vmexit_handler(ctx: &mut VmCpuRegisters) -> bool {
-    use scause::{Exception, Trap};
+/// Runs `vcpu` until its next exit, and handles it. `vcpus` are the other
+/// vCPUs of the guest.
+///
+/// Returns `Some` if the guest cannot go on.
+fn run_guest(
+    vcpu: &mut VCpu,
+    vcpus: &mut [VCpu],
+    config: &GuestConfig,
+    aspace: &mut AddrSpace,
+    mmio: &mut MmioBus,
+) -> Option<VmExit> {
+    CSR.hvip.write_value(vcpu.pending_irqs);
 
+    // The host takes no interrupt from the time the guest is switched in to
+    // the time its exit is read. The host timer interrupt that ends a time
+    // slice stays pending meanwhile, and the host handles it as usual once
+    // interrupts are back on: its timer is re-armed, and no tick is lost.
+    let irqs_enabled = axhal::arch::irqs_enabled();
+    axhal::arch::disable_irqs();
+    arm_host_timer(&vcpu.regs);
+    vcpu.regs.restore_vs_csrs();
+    vcpu.regs.run();
+    vcpu.regs.save_vs_csrs();
     let scause = scause::read();
+    vcpu.regs.trap_csrs = VmCpuTrapState {
+        scause: scause.bits(),
+        stval: stval::read(),
+        htval: CSR.htval.get_value(),
+        htinst: CSR.htinst.get_value(),
+    };
+    if irqs_enabled {
+        axhal::arch::enable_irqs();
+    }
+
+    vmexit_handler(vcpu, scause, vcpus, config, aspace, mmio)
+}
+
+/// Handles the exit of `vcpu` for `scause`.
+fn vmexit_handler(
+    vcpu: &mut VCpu,
+    scause: scause::Scause,
+    vcpus: &mut [VCpu],
+    config: &GuestConfig,
+    aspace: &mut AddrSpace,
+    mmio: &mut MmioBus,
+) -> Option<VmExit> {
+    use scause::{Exception, Interrupt, Trap};
+
+    let VCpu { regs: ctx, hart_id, pending_irqs, .. } = vcpu;
+
     match scause.cause() {
         Trap::Exception(Exception::VirtualSupervisorEnvCall) => {
-            let sbi_msg = SbiMessage::from_regs(ctx.guest_regs.gprs.a_regs()).ok();
-            ax_println!("VmExit Reason: VSuperEcall: {:?}", sbi_msg);
+            let sbi_msg = SbiMessage::from_regs(ctx.gprs().a_regs()).ok();
+            debug!("VmExit Reason: VSuperEcall: {:?}", sbi_msg);
             if let Some(msg) = sbi_msg {
                 match msg {
-                    SbiMessage::Reset(_) => {
-                        let a0 = ctx.guest_regs.gprs.reg(A0);
-                        let a1 = ctx.guest_regs.gprs.reg(A1);
-                        ax_println!("a0 = {:#x}, a1 = {:#x}", a0, a1);
-                        assert_eq!(a0, 0x6688);
-                        assert_eq!(a1, 0x1234);
-                        ax_println!("Shutdown vm normally!");
-                        return true;
+                    SbiMessage::Reset(ResetFunction::Reset { reset_type, reason }) => {
+                        #[cfg(feature = "check-shutdown-args")]
+                        if ctx.reg(A7) == sbi_spec::legacy::LEGACY_SHUTDOWN {
+                            // The legacy call takes no arguments: the test
+                            // guests pass these values to it to be checked.
+                            let a0 = ctx.reg(A0);
+                            let a1 = ctx.reg(A1);
+                            ax_println!("a0 = {:#x}, a1 = {:#x}", a0, a1);
+                            assert_eq!(a0, 0x6688);
+                            assert_eq!(a1, 0x1234);
+                        }
+                        if reset_type == ResetType::Shutdown {
+                            if reason == ResetReason::NoReason {
+                                ax_println!("Shutdown vm normally!");
+                            }
+                            return Some(VmExit::Shutdown(reason));
+                        }
+                        ax_println!("Reboot vm: {:?}, reason: {:?}", reset_type, reason);
+                        return Some(VmExit::Reboot);
+                    },
+                    SbiMessage::SetTimer(stime) => {
+                        set_guest_timer(ctx, pending_irqs, stime);
+                        ctx.set_reg(A0, sbi::SBI_SUCCESS);
+                        skip_trapped_insn(ctx);
+                    },
+                    SbiMessage::PutChar(c) => {
+                        axhal::console::putchar(c as u8);
+                        ctx.set_reg(A0, 0);
+                        skip_trapped_insn(ctx);
                     },
-                    _ => todo!(),
+                    SbiMessage::GetChar => {
+                        // -1 if no input is available
+                        let c = axhal::console::getchar().map_or(usize::MAX, |c| c as usize);
+                        ctx.set_reg(A0, c);
+                        skip_trapped_insn(ctx);
+                    },
+                    SbiMessage::DebugConsole(func) => {
+                        let ret = handle_dbcn(aspace, func);
+                        set_sbi_return(ctx, ret);
+                        skip_trapped_insn(ctx);
+                    },
+                    SbiMessage::Hsm(func) => {
+                        let ret = handle_hsm(*hart_id, vcpus, func);
+                        set_sbi_return(ctx, ret);
+                        skip_trapped_insn(ctx);
+                    },
+                    _ => unsupported_sbi_call(ctx),
                 }
             } else {
-                panic!("bad sbi message! ");
+                unsupported_sbi_call(ctx);
+            }
+        },
+        Trap::Interrupt(Interrupt::SupervisorTimer) => {
+            // Either the guest's timer event or the end of its time slice.
+            // Forward the former as a virtual supervisor timer interrupt,
+            // until the guest sets a new timer.
+            if time::read() >= ctx.vstimecmp() {
+                ctx.set_vstimecmp(usize::MAX);
+                *pending_irqs |= traps::interrupt::VIRTUAL_SUPERVISOR_TIMER;
             }
+            // Let the host tasks run before resuming the guest.
+            axtask::yield_now();
+        },
+        Trap::Interrupt(irq) => {
+            // Interrupts are taken between instructions: the guest resumes at
+            // sepc as is. External interrupts are not forwarded, as the guest
+            // has no interrupt controller to claim and complete them on.
+            debug!("Ignored interrupt: {:?}, sepc: {:#x}", irq, ctx.guest_regs.sepc);
         },
         Trap::Exception(Exception::IllegalInstruction) => {
-            panic!("Bad instruction: {:#x} sepc: {:#x}",
-                stval::read(),
-                ctx.guest_regs.sepc
-            );
+            if emulate_csr_read(ctx, *hart_id) {
+                return None;
+            }
+            // Let the guest's own handler deal with it.
+            let stval = ctx.trap_csrs.stval;
+            if !ctx.inject_exception(scause.bits(), stval) {
+                warn!("Bad instruction: {:#x} sepc: {:#x}, and no guest trap handler",
+                    stval,
+                    ctx.guest_regs.sepc
+                );
+                return Some(guest_fault(ctx, scause.cause()));
+            }
         },
         Trap::Exception(Exception::LoadGuestPageFault) => {
-            panic!("LoadGuestPageFault: stval{:#x} sepc: {:#x}",
-                stval::read(),
-                ctx.guest_regs.sepc
-            );
+            if !handle_page_fault(ctx, config, aspace, mmio, MappingFlags::READ) {
+                return Some(guest_fault(ctx, scause.cause()));
+            }
+        },
+        Trap::Exception(Exception::StoreGuestPageFault) => {
+            if !handle_page_fault(ctx, config, aspace, mmio, MappingFlags::WRITE) {
+                return Some(guest_fault(ctx, scause.cause()));
+            }
+        },
+        Trap::Exception(Exception::InstructionGuestPageFault) => {
+            if !handle_page_fault(ctx, config, aspace, mmio, MappingFlags::EXECUTE) {
+                return Some(guest_fault(ctx, scause.cause()));
+            }
         },
-        _ => {
-            panic!(
-                "Unhandled trap: {:?}, sepc: {:#x}, stval: {:#x}",
+        Trap::Exception(_) => {
+            // Resuming would trap again on the same instruction.
+            warn!("Unhandled exception: {:?}, sepc: {:#x}, stval: {:#x}",
                 scause.cause(),
                 ctx.guest_regs.sepc,
-                stval::read()
+                ctx.trap_csrs.stval
             );
+            return Some(guest_fault(ctx, scause.cause()));
         }
     }
+    None
+}
+
+/// Describes the fault `cause` the guest stopped at.
+fn guest_fault(ctx: &VmCpuRegisters, cause: scause::Trap) -> VmExit {
+    #[cfg(feature = "dump-guest")]
+    dump_guest_state(ctx, cause);
+    VmExit::GuestFault {
+        cause,
+        sepc: ctx.guest_regs.sepc,
+        stval: ctx.trap_csrs.stval,
+    }
+}
+
+/// Prints the guest registers, the trap CSRs and the instruction the guest
+/// stopped at, for debugging a fatal fault `cause`.
+#[cfg(feature = "dump-guest")]
+fn dump_guest_state(ctx: &VmCpuRegisters, cause: scause::Trap) {
+    use scause::{Exception, Trap};
+    const NAMES: [&str; 32] = [
+        "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3",
+        "a4", "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11",
+        "t3", "t4", "t5", "t6",
+    ];
+
+    ax_println!("Guest state at {:?}:", cause);
+    for (i, name) in NAMES.iter().enumerate().skip(1) {
+        let reg = regs::GprIndex::from_raw(i as u32).unwrap();
+        ax_print!("{:>4}: {:#018x}  ", name, ctx.reg(reg));
+        if i % 4 == 3 || i == 31 {
+            ax_println!();
+        }
+    }
+    let regs = &ctx.guest_regs;
+    let trap = &ctx.trap_csrs;
+    ax_println!("sepc: {:#x}, sstatus: {:#x}, hstatus: {:#x}", regs.sepc, regs.sstatus, regs.hstatus);
+    ax_println!("scause: {:#x}, stval: {:#x}, htval: {:#x}, htinst: {:#x}",
+        trap.scause, trap.stval, trap.htval, trap.htinst
+    );
+
+    // Fetching the instruction would fault again if the fetch is what failed.
+    let fetch_failed = matches!(
+        cause,
+        Trap::Exception(
+            Exception::InstructionMisaligned
+                | Exception::InstructionFault
+                | Exception::InstructionPageFault
+                | Exception::InstructionGuestPageFault
+        )
+    );
+    if !fetch_failed {
+        let (insn, len) = insn::trapped_insn(trap.htinst, regs.sepc);
+        ax_println!("insn: {:#0w$x} ({} bytes)", insn, len, w = len * 2 + 2);
+    }
+}
+
+/// Fails an SBI call that the hypervisor does not implement with
+/// `SBI_ERR_NOT_SUPPORTED`, and lets the guest go on.
+fn unsupported_sbi_call(ctx: &mut VmCpuRegisters) {
+    warn!(
+        "Unsupported SBI call: eid {:#x}, fid {:#x}",
+        ctx.reg(A7),
+        ctx.reg(A6)
+    );
+    set_sbi_return(ctx, SbiReturn::error(sbi::SBI_ERR_NOT_SUPPORTED));
+    skip_trapped_insn(ctx);
+}
+
+/// Writes the result of an SBI call to the guest's `a0` and `a1`.
+fn set_sbi_return(ctx: &mut VmCpuRegisters, ret: SbiReturn) {
+    ctx.set_reg(A0, ret.error_code as usize);
+    ctx.set_reg(A1, ret.return_value as usize);
+}
+
+/// Handles a call to the SBI Debug Console extension, with the guest buffers
+/// in `aspace`.
+fn handle_dbcn(aspace: &AddrSpace, func: DebugConsoleFunction) -> SbiReturn {
+    let mut buf = [0u8; 64];
+    match func {
+        DebugConsoleFunction::PutString { len, addr } => {
+            let (len, addr) = (len as usize, addr as usize);
+            let mut written = 0;
+            while written < len {
+                let chunk = &mut buf[..(len - written).min(64)];
+                if aspace.read(VirtAddr::from(addr + written), chunk).is_err() {
+                    return SbiReturn::error(sbi::SBI_ERR_INVALID_PARAM);
+                }
+                axhal::console::write_bytes(chunk);
+                written += chunk.len();
+            }
+            SbiReturn::success(written)
+        }
+        DebugConsoleFunction::GetString { len, addr } => {
+            let len = (len as usize).min(buf.len());
+            let mut read = 0;
+            while read < len {
+                match axhal::console::getchar() {
+                    Some(c) => buf[read] = c,
+                    None => break,
+                }
+                read += 1;
+            }
+            if aspace.write(VirtAddr::from(addr as usize), &buf[..read]).is_err() {
+                return SbiReturn::error(sbi::SBI_ERR_INVALID_PARAM);
+            }
+            SbiReturn::success(read)
+        }
+        DebugConsoleFunction::PutChar(c) => {
+            axhal::console::putchar(c);
+            SbiReturn::success(0)
+        }
+    }
+}
+
+/// Handles a call to the SBI Hart State Management extension from hart
+/// `caller`, with the other harts in `vcpus`.
+fn handle_hsm(caller: usize, vcpus: &mut [VCpu], func: HsmFunction) -> SbiReturn {
+    match func {
+        HsmFunction::HartStart { hart_id, start_addr, opaque } => {
+            if hart_id == caller {
+                return SbiReturn::error(sbi::SBI_ERR_ALREADY_AVAILABLE);
+            }
+            let Some(vcpu) = vcpus.iter_mut().find(|v| v.hart_id == hart_id) else {
+                return SbiReturn::error(sbi::SBI_ERR_INVALID_PARAM);
+            };
+            if vcpu.state == HartState::Started {
+                return SbiReturn::error(sbi::SBI_ERR_ALREADY_AVAILABLE);
+            }
+            reset_vcpu(vcpu, start_addr, opaque);
+            SbiReturn::success(0)
+        }
+        HsmFunction::HartStatus { hart_id } => {
+            if hart_id == caller {
+                return SbiReturn::success(sbi::HART_STATE_STARTED);
+            }
+            match vcpus.iter().find(|v| v.hart_id == hart_id) {
+                Some(v) if v.state == HartState::Started => {
+                    SbiReturn::success(sbi::HART_STATE_STARTED)
+                }
+                Some(_) => SbiReturn::success(sbi::HART_STATE_STOPPED),
+                None => SbiReturn::error(sbi::SBI_ERR_INVALID_PARAM),
+            }
+        }
+    }
+}
+
+/// Sets the guest's next timer event at `stime`, in ticks of the `time` CSR,
+/// and withdraws any pending virtual timer interrupt. The host timer is armed
+/// for it on the next entry to the guest.
+fn set_guest_timer(ctx: &mut VmCpuRegisters, pending_irqs: &mut usize, stime: usize) {
+    ctx.set_vstimecmp(stime);
+    *pending_irqs &= !traps::interrupt::VIRTUAL_SUPERVISOR_TIMER;
+}
+
+/// Arms the host timer for the end of the time slice of the guest, or for its
+/// next timer event if that comes first.
+///
+/// This replaces the next timer event of the host, which is then late by one
+/// time slice at most. The time slice only starts over once it has ended, so
+/// a guest exiting often cannot hold the host timer back.
+fn arm_host_timer(ctx: &VmCpuRegisters) {
+    let now = axhal::time::current_ticks();
+    let mut slice_end = SLICE_END.load(Ordering::Relaxed);
+    if now >= slice_end {
+        slice_end = now + axhal::time::nanos_to_ticks(HOST_TIME_SLICE_NS);
+        SLICE_END.store(slice_end, Ordering::Relaxed);
+    }
+    let deadline = slice_end.min(ctx.vstimecmp() as u64);
+    axhal::time::set_oneshot_timer(axhal::time::ticks_to_nanos(deadline));
+    CSR.sie.read_and_set_bits(traps::interrupt::SUPERVISOR_TIMER);
+}
+
+/// Emulates a guest read of a machine-level ID CSR (`misa`, `mvendorid`,
+/// `marchid`, `mimpid` or `mhartid`), which traps as an illegal instruction
+/// in VS-mode. `hart_id` is the hart id of the guest hart.
+///
+/// Returns `false` if the instruction is not such a read.
+fn emulate_csr_read(ctx: &mut VmCpuRegisters, hart_id: usize) -> bool {
+    let (insn, len) = insn::trapped_insn(ctx.trap_csrs.htinst, ctx.guest_regs.sepc);
+    let Some(read) = insn::decode_csr_read(insn) else {
+        return false;
+    };
+    let val = match read.csr {
+        CSR_MISA => GUEST_MISA,
+        // Not implemented: 0, as allowed by the privileged spec.
+        CSR_MVENDORID | CSR_MARCHID | CSR_MIMPID => 0,
+        CSR_MHARTID => hart_id,
+        _ => return false,
+    };
+    ctx.set_reg(read.rd, val);
+    ctx.guest_regs.sepc += len;
+    true
+}
+
+/// Advances the guest past the instruction that trapped.
+///
+/// The length is decoded from the instruction itself, since it is only 2 bytes
+/// for compressed instructions.
+fn skip_trapped_insn(ctx: &mut VmCpuRegisters) {
+    let (_, len) = insn::trapped_insn(ctx.trap_csrs.htinst, ctx.guest_regs.sepc);
+    ctx.guest_regs.sepc += len;
+}
+
+/// Returns the guest physical address of a guest page fault.
+fn guest_fault_addr(trap: &VmCpuTrapState) -> usize {
+    // htval holds the address shifted right by 2 bits, the low bits come from
+    // the guest virtual address in stval.
+    (trap.htval << 2) | (trap.stval & 0b11)
+}
+
+/// Handles a guest page fault caused by an access of type `access` (`READ` for
+/// loads, `WRITE` for stores and AMOs, `EXECUTE` for instruction fetches).
+///
+/// Loads and stores are first offered to the MMIO devices. Otherwise the page
+/// is mapped on demand, only if it is in the guest RAM of `config` and the
+/// guest memory area
+/// allows `access`: a write to read-only guest memory is a fatal guest fault,
+/// not silently granted. A write to guest RAM outside any memory area maps a
+/// fresh zeroed page there, so the guest can use its RAM without the whole of
+/// it being allocated upfront.
+///
+/// Returns `false` if the fault is fatal for the guest.
+fn handle_page_fault(
+    ctx: &mut VmCpuRegisters,
+    config: &GuestConfig,
+    aspace: &mut AddrSpace,
+    mmio: &mut MmioBus,
+    access: MappingFlags,
+) -> bool {
+    let gpa = guest_fault_addr(&ctx.trap_csrs);
+    if !access.contains(MappingFlags::EXECUTE) && handle_mmio(ctx, mmio, gpa) {
+        return true;
+    }
+    if !config.in_ram(gpa) {
+        warn!("Guest page fault outside guest RAM: {:?} at gpa {:#x}, sepc: {:#x}",
+            access,
+            gpa,
+            ctx.guest_regs.sepc
+        );
+        return false;
+    }
+    if aspace.handle_page_fault(VirtAddr::from(gpa), access)
+        || (access == MappingFlags::WRITE && map_guest_ram_page(aspace, gpa))
+    {
+        // The new mapping is in the G-stage page table.
+        unsafe { core::arch::riscv64::hfence_gvma_all() };
+        return true;
+    }
+    warn!("Guest page fault: {:?} at gpa {:#x}, stval: {:#x}, sepc: {:#x}",
+        access,
+        gpa,
+        ctx.trap_csrs.stval,
+        ctx.guest_regs.sepc
+    );
     false
 }
 
-fn prepare_guest_context(ctx: &mut VmCpuRegisters) {
+/// Maps a fresh zeroed page of guest RAM, readable and writable, at `gpa`.
+///
+/// Returns `false` if the page is already in a memory area, which then denied
+/// the access.
+fn map_guest_ram_page(aspace: &mut AddrSpace, gpa: usize) -> bool {
+    let page = VirtAddr::from(gpa).align_down_4k();
+    let flags = MappingFlags::READ | MappingFlags::WRITE | MappingFlags::USER;
+    aspace.map_alloc(page, PAGE_SIZE_4K, flags, true).is_ok()
+}
+
+/// Emulates a guest load/store to an MMIO device on `mmio`, then advances the
+/// guest past the faulting instruction.
+///
+/// Returns `false` if no device covers `gpa`, or if the faulting instruction
+/// is not a load or store that can be emulated.
+fn handle_mmio(ctx: &mut VmCpuRegisters, mmio: &mut MmioBus, gpa: usize) -> bool {
+    let Some((dev, offset)) = mmio.find(gpa) else {
+        return false;
+    };
+    let (insn, len) = insn::trapped_insn(ctx.trap_csrs.htinst, ctx.guest_regs.sepc);
+    let Some(access) = insn::decode_mem_access(insn) else {
+        warn!("Unsupported MMIO instruction {:#x} at gpa {:#x}, sepc: {:#x}",
+            insn,
+            gpa,
+            ctx.guest_regs.sepc
+        );
+        return false;
+    };
+
+    let bits = access.width * 8;
+    let mask = if bits == usize::BITS as usize {
+        usize::MAX
+    } else {
+        (1 << bits) - 1
+    };
+    if access.is_store {
+        let val = ctx.reg(access.reg) & mask;
+        dev.write(offset, access.width, val);
+    } else {
+        let mut val = dev.read(offset, access.width) & mask;
+        if access.signed && bits < usize::BITS as usize {
+            // sign-extend to XLEN
+            let shift = usize::BITS as usize - bits;
+            val = (((val << shift) as isize) >> shift) as usize;
+        }
+        ctx.set_reg(access.reg, val);
+    }
+    ctx.guest_regs.sepc += len;
+    true
+}
+
+fn prepare_guest_context(vcpu: &mut VCpu, entry: usize) {
     // Set hstatus
     let mut hstatus = LocalRegisterCopy::<usize, hstatus::Register>::new(
         riscv::register::hstatus::read().bits(),
@@ -135,12 +683,38 @@ fn prepare_guest_context(ctx: &mut VmCpuRegisters) {
     // Set SPVP bit in order to accessing VS-mode memory from HS-mode.
     hstatus.modify(hstatus::spvp::Supervisor);
     CSR.hstatus.write_value(hstatus.get());
-    ctx.guest_regs.hstatus = hstatus.get();
+
+    // Let the guest read the `time` CSR directly, and handle the virtual
+    // timer interrupts we inject itself.
+    CSR.hcounteren.write(hcounteren::time::SET);
+    CSR.hideleg.read_and_set_bits(traps::interrupt::VIRTUAL_SUPERVISOR_TIMER);
+
+    reset_vcpu(vcpu, entry, 0);
+}
+
+/// Restarts the guest after a reboot: the boot hart starts over at `entry`, and
+/// the others are stopped. The guest memory is kept as is.
+fn reboot_guest(vcpus: &mut [VCpu], entry: usize) {
+    for vcpu in vcpus.iter_mut() {
+        *vcpu = VCpu::new(vcpu.hart_id);
+    }
+    reset_vcpu(&mut vcpus[0], entry, 0);
+}
+
+/// Starts `vcpu` at `entry` in VS-mode, with its hart id in `a0` and `opaque`
+/// in `a1`, as SBI does for a hart it starts.
+fn reset_vcpu(vcpu: &mut VCpu, entry: usize, opaque: usize) {
+    let ctx = &mut vcpu.regs;
+    ctx.guest_regs.hstatus = CSR.hstatus.get_value();
 
     // Set sstatus in guest mode.
     let mut sstatus = sstatus::read();
     sstatus.set_spp(sstatus::SPP::Supervisor);
-    ctx.guest_regs.sstatus = sstatus.bits();
-    // Return to entry to start vm.
-    ctx.guest_regs.sepc = VM_ENTRY;
+    // SIE only gates the host interrupts, which must stay off until the guest
+    // is entered. They are taken in VS-mode whatever its value.
+    ctx.guest_regs.sstatus = sstatus.bits() & !SSTATUS_SIE;
+    ctx.guest_regs.sepc = entry;
+    ctx.set_reg(A0, vcpu.hart_id);
+    ctx.set_reg(A1, opaque);
+    vcpu.state = HartState::Started;
 }
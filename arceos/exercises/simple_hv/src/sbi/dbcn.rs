@@ -1,3 +1,8 @@
+use axerrno::{AxError, AxResult};
+
+/// Extension ID of the Debug Console extension ("DBCN").
+pub const EID_DBCN: usize = 0x4442434E;
+
 /// Functions for the Debug Console extension
 #[derive(Copy, Clone, Debug)]
 pub enum DebugConsoleFunction {
@@ -8,4 +13,36 @@ pub enum DebugConsoleFunction {
         /// The address of the string.
         addr: u64,
     },
+    /// Reads up to `len` bytes from the system console into the given buffer.
+    GetString {
+        /// The length of the buffer.
+        len: u64,
+        /// The address of the buffer.
+        addr: u64,
+    },
+    /// Prints a single byte to the system console.
+    PutChar(u8),
+}
+
+impl DebugConsoleFunction {
+    /// Attempts to parse `Self` from the passed in `a0-a7`.
+    pub(crate) fn from_regs(args: &[usize]) -> AxResult<Self> {
+        use DebugConsoleFunction::*;
+
+        // The buffer address is split into a1 (low bits) and a2 (high bits),
+        // a2 is only used on RV32.
+        let addr = args[1] as u64 | ((args[2] as u64) << 32);
+        Ok(match args[6] {
+            0 => PutString {
+                len: args[0] as u64,
+                addr,
+            },
+            1 => GetString {
+                len: args[0] as u64,
+                addr,
+            },
+            2 => PutChar(args[0] as u8),
+            _ => return Err(AxError::NotFound),
+        })
+    }
 }
@@ -0,0 +1,42 @@
+use axerrno::{AxError, AxResult};
+
+/// The hart is running.
+pub const HART_STATE_STARTED: usize = 0;
+/// The hart is not running.
+pub const HART_STATE_STOPPED: usize = 1;
+
+/// Functions for the Hart State Management extension
+#[derive(Copy, Clone, Debug)]
+pub enum HsmFunction {
+    /// Starts the given hart at `start_addr` in supervisor mode.
+    HartStart {
+        /// The hart to start.
+        hart_id: usize,
+        /// The physical address the hart starts executing at.
+        start_addr: usize,
+        /// Passed to the hart in `a1`.
+        opaque: usize,
+    },
+    /// Gets the current state of the given hart.
+    HartStatus {
+        /// The hart to query.
+        hart_id: usize,
+    },
+}
+
+impl HsmFunction {
+    /// Attempts to parse `Self` from the passed in `a0-a7`.
+    pub(crate) fn from_regs(args: &[usize]) -> AxResult<Self> {
+        use HsmFunction::*;
+
+        Ok(match args[6] {
+            0 => HartStart {
+                hart_id: args[0],
+                start_addr: args[1],
+                opaque: args[2],
+            },
+            2 => HartStatus { hart_id: args[0] },
+            _ => return Err(AxError::NotFound),
+        })
+    }
+}
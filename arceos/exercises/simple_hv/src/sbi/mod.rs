@@ -2,22 +2,24 @@
 
 mod base;
 mod dbcn;
+mod hsm;
 mod pmu;
 mod rfnc;
 mod srst;
 
 use axerrno::{AxError, AxResult};
 pub use base::BaseFunction;
-use dbcn::DebugConsoleFunction;
+pub use dbcn::DebugConsoleFunction;
+pub use hsm::{HsmFunction, HART_STATE_STARTED, HART_STATE_STOPPED};
 pub use pmu::PmuFunction;
 pub use rfnc::RemoteFenceFunction;
 use sbi_spec;
-pub use srst::ResetFunction;
+pub use srst::{ResetFunction, ResetReason, ResetType};
 
 pub const SBI_SUCCESS: usize = 0;
 pub const SBI_ERR_FAILUER: isize = -1;
 pub const SBI_ERR_NOT_SUPPORTED: isize = -2;
-pub const SBI_ERR_INAVLID_PARAM: isize = -3;
+pub const SBI_ERR_INVALID_PARAM: isize = -3;
 pub const SBI_ERR_DENIED: isize = -4;
 pub const SBI_ERR_INVALID_ADDRESS: isize = -5;
 pub const SBI_ERR_ALREADY_AVAILABLE: isize = -6;
@@ -56,12 +58,32 @@ pub enum SbiMessage {
     DebugConsole(DebugConsoleFunction),
     /// Handles system reset
     Reset(ResetFunction),
+    /// The Hart State Management extension.
+    Hsm(HsmFunction),
     /// The RemoteFence Extension.
     RemoteFence(RemoteFenceFunction),
     /// The PMU Extension
     PMU(PmuFunction),
 }
 
+impl SbiReturn {
+    /// A successful call returning `value`.
+    pub fn success(value: usize) -> Self {
+        Self {
+            error_code: SBI_SUCCESS as i64,
+            return_value: value as i64,
+        }
+    }
+
+    /// A failed call with the given SBI error code.
+    pub fn error(code: isize) -> Self {
+        Self {
+            error_code: code as i64,
+            return_value: 0,
+        }
+    }
+}
+
 impl SbiMessage {
     /// Creates an SbiMessage struct from the given GPRs. Intended for use from the ECALL handler
     /// and passed the saved register state from the calling OS. A7 must contain a valid SBI
@@ -75,16 +97,13 @@ impl SbiMessage {
             sbi_spec::legacy::LEGACY_SHUTDOWN => Ok(SbiMessage::Reset(ResetFunction::shutdown())),
             sbi_spec::time::EID_TIME => Ok(SbiMessage::SetTimer(args[0])),
             sbi_spec::srst::EID_SRST => ResetFunction::from_regs(args).map(SbiMessage::Reset),
+            sbi_spec::hsm::EID_HSM => HsmFunction::from_regs(args).map(SbiMessage::Hsm),
+            dbcn::EID_DBCN => DebugConsoleFunction::from_regs(args).map(SbiMessage::DebugConsole),
             sbi_spec::rfnc::EID_RFNC => {
                 RemoteFenceFunction::from_args(args).map(SbiMessage::RemoteFence)
             }
             sbi_spec::pmu::EID_PMU => PmuFunction::from_regs(args).map(SbiMessage::PMU),
-            _ => {
-                error!("args: {:?}", args);
-                error!("args[7]: {:#x}", args[7]);
-                error!("EID_RFENCE: {:#x}", sbi_spec::rfnc::EID_RFNC);
-                Err(AxError::NotFound)
-            }
+            _ => Err(AxError::NotFound),
         }
     }
 }
@@ -3,6 +3,12 @@ use core::mem::size_of;
 
 use memoffset::offset_of;
 use super::regs::{GeneralPurposeRegisters, GprIndex};
+use crate::csrs::{RiscvCsrTrait, CSR};
+
+// `sstatus`/`vsstatus` bits, to take a trap in VS-mode.
+pub const SSTATUS_SIE: usize = 1 << 1;
+const SSTATUS_SPIE: usize = 1 << 5;
+const SSTATUS_SPP: usize = 1 << 8;
 
 /// Hypervisor GPR and CSR state which must be saved/restored when entering/exiting virtualization.
 #[derive(Default)]
@@ -10,6 +16,7 @@ use super::regs::{GeneralPurposeRegisters, GprIndex};
 struct HypervisorCpuState {
     gprs: GeneralPurposeRegisters,
     sstatus: usize,
+    hstatus: usize,
     scounteren: usize,
     stvec: usize,
     sscratch: usize,
@@ -85,6 +92,151 @@ pub struct VmCpuRegisters {
     pub trap_csrs: VmCpuTrapState,
 }
 
+impl VmCpuRegisters {
+    /// Runs the guest until its next exit.
+    ///
+    /// `_run_guest` swaps the guest `sstatus`, `hstatus` and `sscratch` in, and
+    /// must restore the host values before returning. They are checked after
+    /// every exit, so that a context switch bug shows up at the exit that
+    /// causes it rather than later in the host.
+    pub fn run(&mut self) {
+        let host = HostCsrs::read();
+        unsafe {
+            _run_guest(self);
+        }
+        assert_eq!(
+            HostCsrs::read(),
+            host,
+            "host CSRs not restored on guest exit"
+        );
+    }
+
+    /// Saves the VS-level CSRs of the guest hart, when switching it out.
+    pub fn save_vs_csrs(&mut self) {
+        let csrs = &mut self.vs_csrs;
+        csrs.htimedelta = CSR.htimedelta.get_value();
+        csrs.vsstatus = CSR.vsstatus.get_value();
+        csrs.vsie = CSR.vsie.get_value();
+        csrs.vstvec = CSR.vstvec.get_value();
+        csrs.vsscratch = CSR.vsscratch.get_value();
+        csrs.vsepc = CSR.vsepc.get_value();
+        csrs.vscause = CSR.vscause.get_value();
+        csrs.vstval = CSR.vstval.get_value();
+        csrs.vsatp = CSR.vsatp.get_value();
+    }
+
+    /// Reflects an exception with `cause` and `stval` back to the guest, as if
+    /// the hardware had taken it in VS-mode: the guest resumes at its own trap
+    /// handler with `vsepc`, `vscause`, `vstval` and `vsstatus` set up.
+    ///
+    /// Returns `false` if the guest has no trap vector to take it yet.
+    pub fn inject_exception(&mut self, cause: usize, stval: usize) -> bool {
+        let csrs = &mut self.vs_csrs;
+        if csrs.vstvec == 0 {
+            return false;
+        }
+        csrs.vsepc = self.guest_regs.sepc;
+        csrs.vscause = cause;
+        csrs.vstval = stval;
+
+        let mut vsstatus = csrs.vsstatus & !(SSTATUS_SIE | SSTATUS_SPIE | SSTATUS_SPP);
+        if csrs.vsstatus & SSTATUS_SIE != 0 {
+            vsstatus |= SSTATUS_SPIE;
+        }
+        // The privilege the guest trapped from.
+        if self.guest_regs.sstatus & SSTATUS_SPP != 0 {
+            vsstatus |= SSTATUS_SPP;
+        }
+        csrs.vsstatus = vsstatus;
+
+        // Exceptions always go to the base of the vector, in VS-mode.
+        self.guest_regs.sstatus |= SSTATUS_SPP;
+        self.guest_regs.sepc = csrs.vstvec & !0b11;
+        true
+    }
+
+    /// Restores the VS-level CSRs of the guest hart, when switching it in.
+    pub fn restore_vs_csrs(&self) {
+        let csrs = &self.vs_csrs;
+        CSR.htimedelta.write_value(csrs.htimedelta);
+        CSR.vsstatus.write_value(csrs.vsstatus);
+        CSR.vsie.write_value(csrs.vsie);
+        CSR.vstvec.write_value(csrs.vstvec);
+        CSR.vsscratch.write_value(csrs.vsscratch);
+        CSR.vsepc.write_value(csrs.vsepc);
+        CSR.vscause.write_value(csrs.vscause);
+        CSR.vstval.write_value(csrs.vstval);
+        CSR.vsatp.write_value(csrs.vsatp);
+    }
+
+    /// Returns the guest's general purpose registers.
+    pub fn gprs(&self) -> &GeneralPurposeRegisters {
+        &self.guest_regs.gprs
+    }
+
+    /// Returns the guest's general purpose registers as a mutable.
+    pub fn gprs_mut(&mut self) -> &mut GeneralPurposeRegisters {
+        &mut self.guest_regs.gprs
+    }
+
+    /// Returns the value of the guest register `idx`.
+    pub fn reg(&self, idx: GprIndex) -> usize {
+        self.guest_regs.gprs.reg(idx)
+    }
+
+    /// Sets the guest register `idx` to `val`. Writes to `zero` are ignored.
+    pub fn set_reg(&mut self, idx: GprIndex, val: usize) {
+        self.guest_regs.gprs.set_reg(idx, val);
+    }
+
+    /// Returns the guest's next timer event, in ticks of the `time` CSR.
+    pub fn vstimecmp(&self) -> usize {
+        self.vs_csrs.vstimecmp
+    }
+
+    /// Sets the guest's next timer event, in ticks of the `time` CSR.
+    pub fn set_vstimecmp(&mut self, stime: usize) {
+        self.vs_csrs.vstimecmp = stime;
+    }
+}
+
+/// Whether a guest hart runs, as managed by the SBI HSM extension.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HartState {
+    /// Not started yet.
+    #[default]
+    Stopped,
+    /// Running guest code.
+    Started,
+}
+
+/// A virtual hart of the guest.
+#[derive(Default)]
+pub struct VCpu {
+    /// Register state saved/restored when entering/exiting the guest.
+    pub regs: VmCpuRegisters,
+    /// Hart id of this vCPU, as seen by the guest.
+    pub hart_id: usize,
+    /// Whether the vCPU runs.
+    pub state: HartState,
+    /// Virtual interrupts pending for the guest, as `hvip` bits. They are
+    /// loaded into `hvip` on every entry to the guest.
+    pub pending_irqs: usize,
+}
+
+impl VCpu {
+    /// Creates a vCPU for the guest hart `hart_id`, with zeroed registers and
+    /// no timer event.
+    pub fn new(hart_id: usize) -> Self {
+        let mut vcpu = Self {
+            hart_id,
+            ..Default::default()
+        };
+        vcpu.regs.set_vstimecmp(usize::MAX);
+        vcpu
+    }
+}
+
 #[allow(dead_code)]
 const fn hyp_gpr_offset(index: GprIndex) -> usize {
     offset_of!(VmCpuRegisters, hyp_regs)
@@ -139,6 +291,7 @@ global_asm!(
     hyp_s11 = const hyp_gpr_offset(GprIndex::S11),
     hyp_sp = const hyp_gpr_offset(GprIndex::SP),
     hyp_sstatus = const hyp_csr_offset!(sstatus),
+    hyp_hstatus = const hyp_csr_offset!(hstatus),
     hyp_scounteren = const hyp_csr_offset!(scounteren),
     hyp_stvec = const hyp_csr_offset!(stvec),
     hyp_sscratch = const hyp_csr_offset!(sscratch),
@@ -181,6 +334,27 @@ global_asm!(
 
 );
 
+/// The host CSRs that [`_run_guest`] swaps with the guest ones.
+#[derive(Debug, PartialEq, Eq)]
+struct HostCsrs {
+    sstatus: usize,
+    hstatus: usize,
+    sscratch: usize,
+}
+
+impl HostCsrs {
+    fn read() -> Self {
+        Self {
+            sstatus: riscv::register::sstatus::read().bits(),
+            hstatus: CSR.hstatus.get_value(),
+            sscratch: riscv::register::sscratch::read(),
+        }
+    }
+}
+
 extern "C" {
-    pub fn _run_guest(state: *mut VmCpuRegisters);
+    /// Enters the guest with the state in `state`, and returns on its next
+    /// exit with the host `sstatus`, `hstatus`, `sscratch`, `scounteren` and
+    /// `stvec` restored. Use [`VmCpuRegisters::run`] rather than this.
+    fn _run_guest(state: *mut VmCpuRegisters);
 }
@@ -158,6 +158,17 @@ impl File {
         self.inner.truncate(size)
     }
 
+    /// Attempts to sync all OS-internal metadata to disk.
+    pub fn sync_all(&self) -> Result<()> {
+        self.inner.sync()
+    }
+
+    /// Like [`File::sync_all`], except that it might not synchronize file
+    /// metadata to the filesystem.
+    pub fn sync_data(&self) -> Result<()> {
+        self.inner.sync()
+    }
+
     /// Queries metadata about the underlying file.
     pub fn metadata(&self) -> Result<Metadata> {
         self.inner.get_attr().map(Metadata)
@@ -6,18 +6,43 @@ mod file;
 pub use self::dir::{DirBuilder, DirEntry, ReadDir};
 pub use self::file::{File, FileType, Metadata, OpenOptions, Permissions};
 
-use alloc::{string::String, vec::Vec};
+use alloc::{format, string::String, vec::Vec};
 use axio::{self as io, prelude::*};
+use core::sync::atomic::{AtomicU64, Ordering};
 
 /// Returns an iterator over the entries within a directory.
 pub fn read_dir(path: &str) -> io::Result<ReadDir> {
     ReadDir::new(path)
 }
 
-/// Returns the canonical, absolute form of a path with all intermediate
-/// components normalized.
+/// Returns the canonical, absolute form of a path, with all `.` and `..`
+/// components resolved, relative paths being resolved against the current
+/// directory.
+///
+/// Returns an error if the path does not exist, or if a `..` component goes
+/// above the root directory.
 pub fn canonicalize(path: &str) -> io::Result<String> {
-    crate::root::absolute_path(path)
+    let mut components = Vec::new();
+    if !path.starts_with('/') {
+        let cwd = current_dir()?;
+        components.extend(cwd.split('/').filter(|c| !c.is_empty()));
+    }
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                if components.pop().is_none() {
+                    return Err(io::Error::InvalidInput);
+                }
+            }
+            c => components.push(c),
+        }
+    }
+
+    let mut canonical = String::from("/");
+    canonical.push_str(&components.join("/"));
+    metadata(&canonical)?;
+    Ok(canonical)
 }
 
 /// Returns the current working directory as a [`String`].
@@ -75,6 +100,24 @@ pub fn remove_dir(path: &str) -> io::Result<()> {
     crate::root::remove_dir(None, path)
 }
 
+/// Removes a directory at this path, after removing all its contents.
+///
+/// Sub-directories are removed recursively before their parent.
+pub fn remove_dir_all(path: &str) -> io::Result<()> {
+    // collect first, removing entries while iterating may skip some of them
+    let entries = read_dir(path)?
+        .map(|entry| entry.map(|e| (e.path(), e.file_type())))
+        .collect::<io::Result<Vec<_>>>()?;
+    for (child, ty) in entries {
+        if ty.is_dir() {
+            remove_dir_all(&child)?;
+        } else {
+            remove_file(&child)?;
+        }
+    }
+    remove_dir(path)
+}
+
 /// Removes a file from the filesystem.
 pub fn remove_file(path: &str) -> io::Result<()> {
     crate::root::remove_file(None, path)
@@ -87,3 +130,68 @@ pub fn remove_file(path: &str) -> io::Result<()> {
 pub fn rename(old: &str, new: &str) -> io::Result<()> {
     crate::root::rename(old, new)
 }
+
+/// Copies the contents of one file to another, returns the total number of
+/// bytes copied.
+///
+/// The destination file is created if it does not exist, and will be
+/// truncated if it does.
+pub fn copy(from: &str, to: &str) -> io::Result<u64> {
+    let mut reader = File::open(from)?;
+    let mut writer = File::create(to)?;
+    let mut buf = [0; 512];
+    let mut copied = 0;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(copied);
+        }
+        writer.write_all(&buf[..n])?;
+        copied += n as u64;
+    }
+}
+
+/// The directory in which [`temp_file`] creates its files.
+///
+/// It is the mount point of a ramfs with the `ramfs` feature, and is created
+/// by [`temp_file`] if missing otherwise.
+pub const TEMP_DIR: &str = "/tmp";
+
+/// Creates a new, empty file with a unique name in [`TEMP_DIR`], opened for
+/// reading and writing, and returns its path along with it.
+///
+/// The name is `prefix` followed by a pseudo-random number, drawn again if a
+/// file of that name already exists. The file is not removed automatically.
+pub fn temp_file(prefix: &str) -> io::Result<(String, File)> {
+    create_dir_all(TEMP_DIR)?;
+    loop {
+        let path = format!("{}/{}{:016x}", TEMP_DIR, prefix, temp_suffix());
+        let opened = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path);
+        match opened {
+            Ok(file) => return Ok((path, file)),
+            Err(io::Error::AlreadyExists) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Returns a new suffix for [`temp_file`] on every call.
+///
+/// There is no random number generator at this level, so it scrambles a
+/// global counter mixed with the counter's own address. Names only need to be
+/// unlikely to be taken, as [`temp_file`] skips the taken ones.
+fn temp_suffix() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let seed = &COUNTER as *const _ as u64;
+    // splitmix64
+    let mut z = COUNTER
+        .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+        .wrapping_add(seed);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
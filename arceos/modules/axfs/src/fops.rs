@@ -228,6 +228,18 @@ impl File {
         Ok(())
     }
 
+    /// Synchronizes the file data and metadata to the underlying device.
+    ///
+    /// Unlike [`File::flush`], it does not need write access. Filesystems that
+    /// do not implement `fsync` (e.g. ramfs) have no device to write back to,
+    /// so there is nothing to do for them.
+    pub fn sync(&self) -> AxResult {
+        match self.access_node(Cap::empty())?.fsync() {
+            Err(AxError::InvalidInput | AxError::Unsupported) => Ok(()),
+            res => res,
+        }
+    }
+
     /// Sets the cursor of the file to the specified offset. Returns the new
     /// position after the seek.
     pub fn seek(&mut self, pos: SeekFrom) -> AxResult<u64> {
@@ -85,10 +85,26 @@ impl VfsNodeOps for FileWrapper<'static> {
         file.write(buf).map_err(as_vfs_err)
     }
 
+    fn fsync(&self) -> VfsResult {
+        self.0.lock().flush().map_err(as_vfs_err)
+    }
+
     fn truncate(&self, size: u64) -> VfsResult {
         let mut file = self.0.lock();
-        file.seek(SeekFrom::Start(size)).map_err(as_vfs_err)?; // TODO: more efficient
-        file.truncate().map_err(as_vfs_err)
+        let len = file.seek(SeekFrom::End(0)).map_err(as_vfs_err)?;
+        if size <= len {
+            file.seek(SeekFrom::Start(size)).map_err(as_vfs_err)?; // TODO: more efficient
+            return file.truncate().map_err(as_vfs_err);
+        }
+        // FAT cannot seek past the end, so extend the file with zeros.
+        let zeros = [0; BLOCK_SIZE];
+        let mut remain = size - len;
+        while remain > 0 {
+            let n = remain.min(BLOCK_SIZE as u64) as usize;
+            file.write_all(&zeros[..n]).map_err(as_vfs_err)?;
+            remain -= n as u64;
+        }
+        Ok(())
     }
 }
 
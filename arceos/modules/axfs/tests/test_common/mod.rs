@@ -48,6 +48,94 @@ fn test_read_write_file() -> Result<()> {
     Ok(())
 }
 
+fn test_open_options() -> Result<()> {
+    let fname = "/open-options.txt";
+    println!("test open options {:?}:", fname);
+
+    // create_new on a missing file
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(fname)?;
+    assert_eq!(file.write(b"hello")?, 5);
+    drop(file);
+
+    // create_new on an existing file
+    assert_err!(
+        OpenOptions::new().write(true).create_new(true).open(fname),
+        AlreadyExists
+    );
+
+    // append keeps the existing contents
+    let mut file = OpenOptions::new().append(true).open(fname)?;
+    assert_eq!(file.write(b", world")?, 7);
+    drop(file);
+    assert_eq!(fs::read_to_string(fname)?, "hello, world");
+
+    // truncate drops them
+    let mut file = OpenOptions::new().write(true).truncate(true).open(fname)?;
+    assert_eq!(file.write(b"bye")?, 3);
+    drop(file);
+    assert_eq!(fs::read_to_string(fname)?, "bye");
+
+    // truncate without write access is rejected
+    assert_err!(
+        OpenOptions::new().read(true).truncate(true).open(fname),
+        InvalidInput
+    );
+
+    fs::remove_file(fname)?;
+    println!("test_open_options() OK!");
+    Ok(())
+}
+
+fn test_metadata() -> Result<()> {
+    let fname = "/metadata.txt";
+    let dirname = "/metadata-dir";
+    println!("test metadata {:?} {:?}:", fname, dirname);
+
+    fs::write(fname, [0xaa; 1234])?;
+    let md = fs::metadata(fname)?;
+    println!("metadata of {:?}: {:?}", fname, md);
+    assert_eq!(md.len(), 1234);
+    assert_eq!(md.file_type(), FileType::File);
+    assert!(md.is_file());
+    assert!(!md.is_dir());
+
+    fs::create_dir(dirname)?;
+    let md = fs::metadata(dirname)?;
+    println!("metadata of {:?}: {:?}", dirname, md);
+    assert_eq!(md.file_type(), FileType::Dir);
+    assert!(md.is_dir());
+    assert!(!md.is_file());
+
+    fs::remove_file(fname)?;
+    fs::remove_dir(dirname)?;
+    assert_err!(fs::metadata(fname), NotFound);
+    println!("test_metadata() OK!");
+    Ok(())
+}
+
+fn test_read_write_helpers() -> Result<()> {
+    let fname = "/helpers.bin";
+    println!("test read/write helpers {:?}:", fname);
+
+    // binary round trip, larger than a single block
+    let data = (0..5000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    fs::write(fname, &data)?;
+    assert_eq!(fs::read(fname)?, data);
+    assert_err!(fs::read_to_string(fname), InvalidData);
+
+    // text round trip, `write` truncates the previous contents
+    fs::write(fname, "Rust is cool!\n")?;
+    assert_eq!(fs::read_to_string(fname)?, "Rust is cool!\n");
+    assert_eq!(fs::metadata(fname)?.len(), 14);
+
+    fs::remove_file(fname)?;
+    println!("test_read_write_helpers() OK!");
+    Ok(())
+}
+
 fn test_read_dir() -> Result<()> {
     let dir = "/././//./";
     println!("list directory {:?}:", dir);
@@ -59,6 +147,43 @@ fn test_read_dir() -> Result<()> {
     Ok(())
 }
 
+fn test_read_dir_entries() -> Result<()> {
+    let dir = "/read-dir-test";
+    println!("test read_dir entries {:?}:", dir);
+
+    fs::create_dir(dir)?;
+    fs::write("/read-dir-test/a.txt", "a")?;
+    fs::write("/read-dir-test/b.txt", "b")?;
+    fs::create_dir("/read-dir-test/c")?;
+
+    let mut entries = fs::read_dir(dir)?
+        .map(|e| e.unwrap())
+        .map(|e| (e.file_name(), e.path(), e.file_type()))
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    println!("entries = {:?}", entries);
+    let expected = [
+        ("a.txt", "/read-dir-test/a.txt", FileType::File),
+        ("b.txt", "/read-dir-test/b.txt", FileType::File),
+        ("c", "/read-dir-test/c", FileType::Dir),
+    ];
+    assert_eq!(entries.len(), expected.len());
+    for ((name, path, ty), (exp_name, exp_path, exp_ty)) in entries.iter().zip(expected) {
+        assert_eq!(name, exp_name);
+        assert_eq!(path, exp_path);
+        assert_eq!(*ty, exp_ty);
+    }
+
+    fs::remove_file("/read-dir-test/a.txt")?;
+    fs::remove_file("/read-dir-test/b.txt")?;
+    fs::remove_dir("/read-dir-test/c")?;
+    assert_eq!(fs::read_dir(dir)?.count(), 0); // no `.` or `..`
+    fs::remove_dir(dir)?;
+
+    println!("test_read_dir_entries() OK!");
+    Ok(())
+}
+
 fn test_file_permission() -> Result<()> {
     let fname = "./short.txt";
     println!("test permission {:?}:", fname);
@@ -131,6 +256,36 @@ fn test_create_file_dir() -> Result<()> {
     Ok(())
 }
 
+fn test_create_dir_all() -> Result<()> {
+    let dirname = "/create-all/a/b/c";
+    println!("test create_dir_all {:?}:", dirname);
+
+    // create from scratch
+    assert_err!(fs::metadata("/create-all"), NotFound);
+    fs::create_dir_all(dirname)?;
+    assert!(fs::metadata(dirname)?.is_dir());
+    assert!(fs::metadata("/create-all/a/b")?.is_dir());
+
+    // already exists
+    fs::create_dir_all(dirname)?;
+    fs::create_dir_all("/create-all/a/")?;
+
+    // a component is a regular file
+    fs::write("/create-all/file", "not a dir")?;
+    assert_err!(fs::create_dir_all("/create-all/file/d/e"));
+    assert_err!(fs::create_dir_all("/create-all/file"), AlreadyExists);
+    assert!(fs::metadata("/create-all/file")?.is_file());
+
+    fs::remove_file("/create-all/file")?;
+    fs::remove_dir("/create-all/a/b/c")?;
+    fs::remove_dir("/create-all/a/b")?;
+    fs::remove_dir("/create-all/a")?;
+    fs::remove_dir("/create-all")?;
+
+    println!("test_create_dir_all() OK!");
+    Ok(())
+}
+
 fn test_remove_file_dir() -> Result<()> {
     // remove a file and test existence
     let fname = "//very-long-dir-name/..///new-file.txt";
@@ -164,6 +319,235 @@ fn test_remove_file_dir() -> Result<()> {
     Ok(())
 }
 
+fn test_remove_dir_all() -> Result<()> {
+    let dirname = "/remove-all";
+    println!("test remove_dir_all {:?}:", dirname);
+
+    // an empty directory
+    fs::create_dir(dirname)?;
+    fs::remove_dir_all(dirname)?;
+    assert_err!(fs::metadata(dirname), NotFound);
+
+    // a nested tree
+    fs::create_dir_all("/remove-all/a/b/c")?;
+    fs::create_dir_all("/remove-all/d")?;
+    fs::write("/remove-all/top.txt", "top")?;
+    fs::write("/remove-all/a/b/mid.txt", "mid")?;
+    fs::write("/remove-all/a/b/c/leaf.txt", "leaf")?;
+    fs::remove_dir_all(dirname)?;
+    assert_err!(fs::metadata(dirname), NotFound);
+
+    assert_err!(fs::remove_dir_all(dirname), NotFound);
+
+    println!("test_remove_dir_all() OK!");
+    Ok(())
+}
+
+fn test_copy() -> Result<()> {
+    let (src, dst) = ("/copy-src.txt", "/copy-dst.txt");
+    println!("test copy {:?} to {:?}:", src, dst);
+
+    // copy creates the destination
+    let contents = "x".repeat(1300);
+    fs::write(src, &contents)?;
+    assert_eq!(fs::copy(src, dst)?, 1300);
+    assert_eq!(fs::read_to_string(dst)?, contents);
+
+    // copy truncates an existing destination
+    fs::write(src, "short")?;
+    assert_eq!(fs::copy(src, dst)?, 5);
+    assert_eq!(fs::read_to_string(dst)?, "short");
+
+    fs::remove_file(src)?;
+    assert_err!(fs::copy(src, dst), NotFound);
+
+    fs::remove_file(dst)?;
+    println!("test_copy() OK!");
+    Ok(())
+}
+
+fn test_buf_reader_lines() -> Result<()> {
+    let fname = "/lines.txt";
+    println!("test reading {:?} line by line:", fname);
+
+    // a line longer than the buffer, and no newline at the end
+    let long = "y".repeat(1500);
+    let lines = ["first", "", long.as_str(), "last"];
+    fs::write(fname, lines.join("\n"))?;
+
+    let mut reader = io::BufReader::new(File::open(fname)?);
+    let mut line = String::new();
+    for (i, expected) in lines.iter().enumerate() {
+        line.clear();
+        let newline = if i + 1 < lines.len() { "\n" } else { "" };
+        assert_eq!(reader.read_line(&mut line)?, expected.len() + newline.len());
+        assert_eq!(line, format!("{}{}", expected, newline));
+    }
+    line.clear();
+    assert_eq!(reader.read_line(&mut line)?, 0);
+    assert!(line.is_empty());
+
+    fs::remove_file(fname)?;
+    println!("test_buf_reader_lines() OK!");
+    Ok(())
+}
+
+fn test_set_len() -> Result<()> {
+    let fname = "/set-len.txt";
+    println!("test set_len {:?}:", fname);
+
+    fs::write(fname, "Hello, world!")?;
+    let file = OpenOptions::new().read(true).write(true).open(fname)?;
+
+    // shrinking drops the tail
+    file.set_len(5)?;
+    assert_eq!(file.metadata()?.len(), 5);
+    assert_eq!(fs::read_to_string(fname)?, "Hello");
+
+    // growing fills with zeros
+    file.set_len(1000)?;
+    assert_eq!(file.metadata()?.len(), 1000);
+    let contents = fs::read(fname)?;
+    assert_eq!(&contents[..5], b"Hello");
+    assert!(contents[5..].iter().all(|&b| b == 0));
+
+    drop(file);
+    fs::remove_file(fname)?;
+    println!("test_set_len() OK!");
+    Ok(())
+}
+
+fn test_sync() -> Result<()> {
+    let fname = "/sync.txt";
+    println!("test sync {:?}:", fname);
+
+    let mut file = File::create(fname)?;
+    file.write_all(b"durable data")?;
+    file.sync_all()?;
+    file.sync_data()?;
+    drop(file);
+    assert_eq!(fs::read_to_string(fname)?, "durable data");
+
+    // syncing does not need write access
+    File::open(fname)?.sync_all()?;
+
+    fs::remove_file(fname)?;
+    println!("test_sync() OK!");
+    Ok(())
+}
+
+fn test_current_dir() -> Result<()> {
+    let dirname = "/cwd-test";
+    println!("test current dir {:?}:", dirname);
+    assert_eq!(fs::current_dir()?, "/");
+
+    fs::create_dir(dirname)?;
+    fs::write("/cwd-test/abs.txt", "absolute")?;
+    fs::set_current_dir(dirname)?;
+    assert_eq!(fs::current_dir()?, "/cwd-test/");
+
+    // relative paths resolve under the current directory
+    assert_eq!(fs::read_to_string("abs.txt")?, "absolute");
+    fs::write("./rel.txt", "relative")?;
+    assert_eq!(fs::read_to_string("/cwd-test/rel.txt")?, "relative");
+
+    // error cases leave the current directory unchanged
+    assert_err!(fs::set_current_dir("not-exist"), NotFound);
+    assert_err!(fs::set_current_dir("rel.txt"), NotADirectory);
+    assert_eq!(fs::current_dir()?, "/cwd-test/");
+
+    fs::set_current_dir("..")?;
+    assert_eq!(fs::current_dir()?, "/");
+    fs::remove_file("/cwd-test/abs.txt")?;
+    fs::remove_file("cwd-test/rel.txt")?;
+    fs::remove_dir(dirname)?;
+
+    println!("test_current_dir() OK!");
+    Ok(())
+}
+
+fn test_canonicalize() -> Result<()> {
+    let dirname = "/canon-test";
+    println!("test canonicalize {:?}:", dirname);
+
+    fs::create_dir_all("/canon-test/a/b")?;
+    fs::create_dir("/canon-test/a/c")?;
+    fs::write("/canon-test/a/c/file.txt", "c")?;
+
+    // absolute paths
+    assert_eq!(
+        fs::canonicalize("/canon-test/a/./b/../c")?,
+        "/canon-test/a/c"
+    );
+    assert_eq!(
+        fs::canonicalize("//canon-test/./a//c/file.txt")?,
+        "/canon-test/a/c/file.txt"
+    );
+    assert_eq!(fs::canonicalize("/./")?, "/");
+
+    // relative paths resolve under the current directory
+    fs::set_current_dir("/canon-test/a")?;
+    assert_eq!(
+        fs::canonicalize("./b/../c/file.txt")?,
+        "/canon-test/a/c/file.txt"
+    );
+    assert_eq!(fs::canonicalize(".")?, "/canon-test/a");
+    assert_eq!(fs::canonicalize("..")?, "/canon-test");
+    assert_eq!(fs::canonicalize("../a/b/./")?, "/canon-test/a/b");
+
+    // error cases
+    assert_err!(fs::canonicalize("b/../missing"), NotFound);
+    assert_err!(fs::canonicalize("../../.."), InvalidInput);
+    assert_err!(fs::canonicalize("/.."), InvalidInput);
+
+    fs::set_current_dir("/")?;
+    fs::remove_dir_all(dirname)?;
+    println!("test_canonicalize() OK!");
+    Ok(())
+}
+
+fn test_temp_file() -> Result<()> {
+    let prefix = "temp-test-";
+    println!("test temp_file {:?} in {:?}:", prefix, fs::TEMP_DIR);
+
+    let (path1, mut file1) = fs::temp_file(prefix)?;
+    let (path2, file2) = fs::temp_file(prefix)?;
+    assert_ne!(path1, path2);
+    for path in [&path1, &path2] {
+        let suffix = path.strip_prefix("/tmp/temp-test-").unwrap();
+        assert_eq!(suffix.len(), 16);
+        assert!(suffix.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    // opened for reading and writing, and empty
+    assert_eq!(file1.read_to_end(&mut Vec::new())?, 0);
+    file1.write_all(b"temp")?;
+    drop(file1);
+    drop(file2);
+    assert_eq!(fs::read_to_string(&path1)?, "temp");
+
+    fs::remove_file(&path1)?;
+    fs::remove_file(&path2)?;
+    println!("test_temp_file() OK!");
+    Ok(())
+}
+
+/// Not part of [`test_all`], since not every filesystem supports renaming.
+#[allow(dead_code)]
+pub fn test_rename() -> Result<()> {
+    let (src, dst) = ("/rename-src.txt", "/rename-dst.txt");
+    println!("test rename {:?} to {:?}:", src, dst);
+
+    fs::write(src, "Rust is cool!\n")?;
+    fs::rename(src, dst)?;
+    assert_err!(fs::metadata(src), NotFound);
+    assert_eq!(fs::read_to_string(dst)?, "Rust is cool!\n");
+
+    fs::remove_file(dst)?;
+    println!("test_rename() OK!");
+    Ok(())
+}
+
 fn test_devfs_ramfs() -> Result<()> {
     const N: usize = 32;
     let mut buf = [1; N];
@@ -254,9 +638,22 @@ fn test_devfs_ramfs() -> Result<()> {
 
 pub fn test_all() {
     test_read_write_file().expect("test_read_write_file() failed");
+    test_open_options().expect("test_open_options() failed");
+    test_metadata().expect("test_metadata() failed");
+    test_read_write_helpers().expect("test_read_write_helpers() failed");
     test_read_dir().expect("test_read_dir() failed");
+    test_read_dir_entries().expect("test_read_dir_entries() failed");
     test_file_permission().expect("test_file_permission() failed");
     test_create_file_dir().expect("test_create_file_dir() failed");
+    test_create_dir_all().expect("test_create_dir_all() failed");
     test_remove_file_dir().expect("test_remove_file_dir() failed");
+    test_remove_dir_all().expect("test_remove_dir_all() failed");
+    test_copy().expect("test_copy() failed");
+    test_buf_reader_lines().expect("test_buf_reader_lines() failed");
+    test_set_len().expect("test_set_len() failed");
+    test_sync().expect("test_sync() failed");
+    test_current_dir().expect("test_current_dir() failed");
+    test_canonicalize().expect("test_canonicalize() failed");
+    test_temp_file().expect("test_temp_file() failed");
     test_devfs_ramfs().expect("test_devfs_ramfs() failed");
 }
@@ -24,4 +24,5 @@ fn test_fatfs() {
     axfs::init_filesystems(AxDeviceContainer::from_one(disk));
 
     test_common::test_all();
+    test_common::test_rename().expect("test_rename() failed");
 }
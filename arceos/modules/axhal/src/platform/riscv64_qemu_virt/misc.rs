@@ -1,7 +1,19 @@
 /// Shutdown the whole system, including all CPUs.
 pub fn terminate() -> ! {
-    info!("Shutting down...");
-    sbi_rt::system_reset(sbi_rt::Shutdown, sbi_rt::NoReason);
+    terminate_with_code(0)
+}
+
+/// Shutdown the whole system, including all CPUs, with the given exit code.
+///
+/// A non-zero `exit_code` is reported to the SBI firmware as a system failure,
+/// which makes QEMU exit with status 1, whatever the code is.
+pub fn terminate_with_code(exit_code: i32) -> ! {
+    info!("Shutting down... (exit code {})", exit_code);
+    if exit_code == 0 {
+        sbi_rt::system_reset(sbi_rt::Shutdown, sbi_rt::NoReason);
+    } else {
+        sbi_rt::system_reset(sbi_rt::Shutdown, sbi_rt::SystemFailure);
+    }
     warn!("It should shutdown!");
     loop {
         crate::arch::halt();
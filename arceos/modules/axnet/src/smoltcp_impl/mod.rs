@@ -11,6 +11,7 @@ use core::ops::DerefMut;
 
 use axdriver::prelude::*;
 use axdriver_net::{DevError, NetBufPtr};
+use axerrno::{ax_err, AxResult};
 use axhal::time::{wall_time_nanos, NANOS_PER_MICROS};
 use axsync::Mutex;
 use lazyinit::LazyInit;
@@ -49,6 +50,8 @@ const TCP_TX_BUF_LEN: usize = 64 * 1024;
 const UDP_RX_BUF_LEN: usize = 64 * 1024;
 const UDP_TX_BUF_LEN: usize = 64 * 1024;
 const LISTEN_QUEUE_SIZE: usize = 512;
+/// The TTL of sockets that did not set one, as smoltcp uses by default.
+const DEFAULT_TTL: u32 = 64;
 
 static LISTEN_TABLE: LazyInit<ListenTable> = LazyInit::new();
 static SOCKET_SET: LazyInit<SocketSetWrapper> = LazyInit::new();
@@ -163,6 +166,11 @@ impl InterfaceWrapper {
         });
     }
 
+    /// Returns whether `addr` is a broadcast address on this interface.
+    pub fn is_broadcast(&self, addr: IpAddress) -> bool {
+        is_broadcast_in(addr, self.iface.lock().ip_addrs())
+    }
+
     pub fn setup_gateway(&self, gateway: IpAddress) {
         let mut iface = self.iface.lock();
         match gateway {
@@ -273,6 +281,23 @@ impl<'a> TxToken for AxNetTxToken<'a> {
     }
 }
 
+/// Converts a socket TTL to a smoltcp hop limit, which must be in `1..=255`.
+fn ttl_to_hop_limit(ttl: u32) -> AxResult<u8> {
+    match u8::try_from(ttl) {
+        Ok(hop_limit) if hop_limit > 0 => Ok(hop_limit),
+        _ => ax_err!(InvalidInput, "invalid TTL"),
+    }
+}
+
+/// Returns whether `addr` is the limited broadcast address, or the directed
+/// broadcast address of one of the subnets `cidrs`.
+fn is_broadcast_in(addr: IpAddress, cidrs: &[IpCidr]) -> bool {
+    addr.is_broadcast()
+        || cidrs.iter().any(|cidr| match (cidr, addr) {
+            (IpCidr::Ipv4(cidr), IpAddress::Ipv4(addr)) => cidr.broadcast() == Some(addr),
+        })
+}
+
 fn snoop_tcp_packet(buf: &[u8], sockets: &mut SocketSet<'_>) -> Result<(), smoltcp::wire::Error> {
     use smoltcp::wire::{EthernetFrame, IpProtocol, Ipv4Packet, TcpPacket};
 
@@ -328,3 +353,18 @@ pub(crate) fn init(net_dev: AxNetDevice) {
     info!("  ip:       {}/{}", ip, IP_PREFIX);
     info!("  gateway:  {}", gateway);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_broadcast_in() {
+        let cidrs = [IpCidr::new(IpAddress::v4(10, 0, 2, 15), 24)];
+        assert!(is_broadcast_in(IpAddress::v4(255, 255, 255, 255), &cidrs));
+        assert!(is_broadcast_in(IpAddress::v4(10, 0, 2, 255), &cidrs));
+        assert!(!is_broadcast_in(IpAddress::v4(10, 0, 2, 2), &cidrs));
+        assert!(!is_broadcast_in(IpAddress::v4(10, 0, 3, 255), &cidrs));
+        assert!(!is_broadcast_in(IpAddress::v4(10, 0, 2, 255), &[]));
+    }
+}
@@ -11,7 +11,7 @@ use smoltcp::socket::tcp::{self, ConnectError, State};
 use smoltcp::wire::{IpEndpoint, IpListenEndpoint};
 
 use super::addr::{from_core_sockaddr, into_core_sockaddr, is_unspecified, UNSPECIFIED_ENDPOINT};
-use super::{SocketSetWrapper, ETH0, LISTEN_TABLE, SOCKET_SET};
+use super::{ttl_to_hop_limit, SocketSetWrapper, DEFAULT_TTL, ETH0, LISTEN_TABLE, SOCKET_SET};
 
 // State transitions:
 // CLOSED -(connect)-> BUSY -> CONNECTING -> CONNECTED -(shutdown)-> BUSY -> CLOSED
@@ -41,6 +41,7 @@ pub struct TcpSocket {
     local_addr: UnsafeCell<IpEndpoint>,
     peer_addr: UnsafeCell<IpEndpoint>,
     nonblock: AtomicBool,
+    read_shut: AtomicBool,
 }
 
 unsafe impl Sync for TcpSocket {}
@@ -54,6 +55,7 @@ impl TcpSocket {
             local_addr: UnsafeCell::new(UNSPECIFIED_ENDPOINT),
             peer_addr: UnsafeCell::new(UNSPECIFIED_ENDPOINT),
             nonblock: AtomicBool::new(false),
+            read_shut: AtomicBool::new(false),
         }
     }
 
@@ -69,6 +71,7 @@ impl TcpSocket {
             local_addr: UnsafeCell::new(local_addr),
             peer_addr: UnsafeCell::new(peer_addr),
             nonblock: AtomicBool::new(false),
+            read_shut: AtomicBool::new(false),
         }
     }
 
@@ -115,6 +118,29 @@ impl TcpSocket {
         self.nonblock.store(nonblocking, Ordering::Release);
     }
 
+    /// Returns the time-to-live of the IP packets sent from this socket.
+    pub fn ttl(&self) -> AxResult<u32> {
+        // SAFETY: `self.handle` should be initialized in a connected socket.
+        let handle = unsafe { self.handle.get().read() }.ok_or(AxError::NotConnected)?;
+        let hop_limit =
+            SOCKET_SET.with_socket::<tcp::Socket, _, _>(handle, |socket| socket.hop_limit());
+        Ok(hop_limit.map_or(DEFAULT_TTL, u32::from))
+    }
+
+    /// Sets the time-to-live of the IP packets sent from this socket.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if `ttl` is not in
+    /// `1..=255`.
+    pub fn set_ttl(&self, ttl: u32) -> AxResult {
+        let hop_limit = ttl_to_hop_limit(ttl)?;
+        // SAFETY: `self.handle` should be initialized in a connected socket.
+        let handle = unsafe { self.handle.get().read() }.ok_or(AxError::NotConnected)?;
+        SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+            socket.set_hop_limit(Some(hop_limit));
+        });
+        Ok(())
+    }
+
     /// Connects to the given address and port.
     ///
     /// The local port is generated automatically.
@@ -269,12 +295,43 @@ impl TcpSocket {
         Ok(())
     }
 
+    /// Shuts down the read half of the connection.
+    ///
+    /// Later receives return 0 (EOF), sending goes on.
+    pub fn shutdown_read(&self) -> AxResult {
+        if !self.is_connected() {
+            return ax_err!(NotConnected, "socket shutdown() failed");
+        }
+        self.read_shut.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Shuts down the write half of the connection.
+    ///
+    /// A FIN is sent, so the peer reads EOF once it received all the data
+    /// sent before. Receiving goes on.
+    pub fn shutdown_write(&self) -> AxResult {
+        if !self.is_connected() {
+            return ax_err!(NotConnected, "socket shutdown() failed");
+        }
+        // SAFETY: `self.handle` should be initialized in a connected socket.
+        let handle = unsafe { self.handle.get().read().unwrap() };
+        SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+            debug!("TCP socket {}: shutting down writing", handle);
+            socket.close();
+        });
+        SOCKET_SET.poll_interfaces();
+        Ok(())
+    }
+
     /// Receives data from the socket, stores it in the given buffer.
     pub fn recv(&self, buf: &mut [u8]) -> AxResult<usize> {
         if self.is_connecting() {
             return Err(AxError::WouldBlock);
         } else if !self.is_connected() {
             return ax_err!(NotConnected, "socket recv() failed");
+        } else if self.read_shut.load(Ordering::Acquire) {
+            return Ok(0);
         }
 
         // SAFETY: `self.handle` should be initialized in a connected socket.
@@ -11,7 +11,7 @@ use smoltcp::socket::udp::{self, BindError, SendError};
 use smoltcp::wire::{IpEndpoint, IpListenEndpoint};
 
 use super::addr::{from_core_sockaddr, into_core_sockaddr, is_unspecified, UNSPECIFIED_ENDPOINT};
-use super::{SocketSetWrapper, SOCKET_SET};
+use super::{ttl_to_hop_limit, SocketSetWrapper, DEFAULT_TTL, ETH0, SOCKET_SET};
 
 /// A UDP socket that provides POSIX-like APIs.
 pub struct UdpSocket {
@@ -19,6 +19,7 @@ pub struct UdpSocket {
     local_addr: RwLock<Option<IpEndpoint>>,
     peer_addr: RwLock<Option<IpEndpoint>>,
     nonblock: AtomicBool,
+    broadcast: AtomicBool,
 }
 
 impl UdpSocket {
@@ -32,6 +33,7 @@ impl UdpSocket {
             local_addr: RwLock::new(None),
             peer_addr: RwLock::new(None),
             nonblock: AtomicBool::new(false),
+            broadcast: AtomicBool::new(false),
         }
     }
 
@@ -69,6 +71,41 @@ impl UdpSocket {
         self.nonblock.store(nonblocking, Ordering::Release);
     }
 
+    /// Returns whether this socket may send to broadcast addresses.
+    #[inline]
+    pub fn is_broadcast(&self) -> bool {
+        self.broadcast.load(Ordering::Acquire)
+    }
+
+    /// Allows or forbids sending to broadcast addresses (`SO_BROADCAST`).
+    ///
+    /// It is forbidden by default, and sending to a broadcast address, either
+    /// 255.255.255.255 or that of the interface's subnet, returns
+    /// [`Err(PermissionDenied)`](AxError::PermissionDenied).
+    #[inline]
+    pub fn set_broadcast(&self, broadcast: bool) {
+        self.broadcast.store(broadcast, Ordering::Release);
+    }
+
+    /// Returns the time-to-live of the IP packets sent from this socket.
+    pub fn ttl(&self) -> u32 {
+        SOCKET_SET.with_socket::<udp::Socket, _, _>(self.handle, |socket| {
+            socket.hop_limit().map_or(DEFAULT_TTL, u32::from)
+        })
+    }
+
+    /// Sets the time-to-live of the IP packets sent from this socket.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if `ttl` is not in
+    /// `1..=255`.
+    pub fn set_ttl(&self, ttl: u32) -> AxResult {
+        let hop_limit = ttl_to_hop_limit(ttl)?;
+        SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
+            socket.set_hop_limit(Some(hop_limit));
+        });
+        Ok(())
+    }
+
     /// Binds an unbound socket to the given address and port.
     ///
     /// It's must be called before [`send_to`](Self::send_to) and
@@ -210,6 +247,12 @@ impl UdpSocket {
         if self.local_addr.read().is_none() {
             return ax_err!(NotConnected, "socket send() failed");
         }
+        if ETH0.is_broadcast(remote_endpoint.addr) && !self.is_broadcast() {
+            return ax_err!(
+                PermissionDenied,
+                "socket send() failed: broadcast not allowed"
+            );
+        }
 
         self.block_on(|| {
             SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
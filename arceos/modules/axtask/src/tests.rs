@@ -32,6 +32,36 @@ fn test_sched_fifo() {
     }
 }
 
+#[test]
+fn test_yield_alternation() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_ROUNDS: usize = 5;
+    static TRACE: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+    static FINISHED_TASKS: AtomicUsize = AtomicUsize::new(0);
+
+    for i in 0..2 {
+        axtask::spawn(move || {
+            for _ in 0..NUM_ROUNDS {
+                TRACE.lock().unwrap().push(i);
+                axtask::yield_now();
+            }
+            FINISHED_TASKS.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+
+    while FINISHED_TASKS.load(Ordering::Relaxed) < 2 {
+        axtask::yield_now();
+    }
+    let trace = TRACE.lock().unwrap();
+    println!("yield_alternation: {:?}", *trace);
+    assert_eq!(trace.len(), 2 * NUM_ROUNDS);
+    for (n, &i) in trace.iter().enumerate() {
+        assert_eq!(i, n % 2); // each task gives the CPU to the other one
+    }
+}
+
 #[test]
 fn test_fp_state_switch() {
     let _lock = SERIAL.lock();
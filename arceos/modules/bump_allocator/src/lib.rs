@@ -1,6 +1,8 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
-use allocator::{BaseAllocator, ByteAllocator, PageAllocator};
+use allocator::{AllocError, AllocResult, BaseAllocator, ByteAllocator, PageAllocator};
+use core::alloc::Layout;
+use core::ptr::NonNull;
 
 /// Early memory allocator
 /// Use it before formal bytes-allocator and pages-allocator can work!
@@ -16,16 +18,226 @@ use allocator::{BaseAllocator, ByteAllocator, PageAllocator};
 /// When it goes down to ZERO, free bytes-used area.
 /// For pages area, it will never be freed!
 ///
-pub struct EarlyAllocator;
+/// `PAGE_SIZE` must be a power of two, which is checked at build time:
+///
+/// ```compile_fail
+/// use bump_allocator::EarlyAllocator;
+///
+/// let _ = EarlyAllocator::<3>::new();
+/// ```
+pub struct EarlyAllocator<const PAGE_SIZE: usize> {
+    start: usize,
+    end: usize,
+    b_pos: usize,
+    p_pos: usize,
+    count: usize,
+    peak_bytes: usize,
+    peak_pages: usize,
+}
+
+impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
+    /// Fails the build for a `PAGE_SIZE` that is not a power of two, once
+    /// evaluated by [`new`](Self::new).
+    const PAGE_SIZE_CHECK: () = assert!(
+        PAGE_SIZE.is_power_of_two(),
+        "PAGE_SIZE must be a power of two"
+    );
+
+    /// Creates an empty allocator. It has no memory until [`init`] is called.
+    ///
+    /// [`init`]: BaseAllocator::init
+    pub const fn new() -> Self {
+        let () = Self::PAGE_SIZE_CHECK;
+        Self {
+            start: 0,
+            end: 0,
+            b_pos: 0,
+            p_pos: 0,
+            count: 0,
+            peak_bytes: 0,
+            peak_pages: 0,
+        }
+    }
+
+    /// Returns the most bytes ever in use in the bytes area.
+    pub fn peak_bytes_used(&self) -> usize {
+        self.peak_bytes
+    }
+
+    /// Returns the most pages ever in use in the pages area.
+    pub fn peak_pages_used(&self) -> usize {
+        self.peak_pages
+    }
+
+    /// Frees the most recent byte allocation, as a stack would.
+    ///
+    /// If `pos` with `layout` ends exactly at the top of the bytes area, the
+    /// top is rolled back to `pos` so that the space can be allocated again,
+    /// and `true` is returned. Otherwise nothing changes and `false` is
+    /// returned.
+    pub fn try_dealloc_top(&mut self, pos: NonNull<u8>, layout: Layout) -> bool {
+        let pos = pos.as_ptr() as usize;
+        if self.count == 0 || pos + layout.size() != self.b_pos {
+            return false;
+        }
+        self.b_pos = pos;
+        self.count -= 1;
+        if self.count == 0 {
+            self.b_pos = self.start;
+        }
+        true
+    }
+}
+
+impl<const PAGE_SIZE: usize> Default for EarlyAllocator<PAGE_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const PAGE_SIZE: usize> BaseAllocator for EarlyAllocator<PAGE_SIZE> {
+    fn init(&mut self, start: usize, size: usize) {
+        self.start = start;
+        self.end = start + size;
+        self.b_pos = start;
+        self.p_pos = self.end;
+        self.count = 0;
+        self.peak_bytes = 0;
+        self.peak_pages = 0;
+    }
+
+    fn add_memory(&mut self, _start: usize, _size: usize) -> AllocResult {
+        Err(AllocError::NoMemory)
+    }
+}
+
+impl<const PAGE_SIZE: usize> ByteAllocator for EarlyAllocator<PAGE_SIZE> {
+    fn alloc(&mut self, layout: Layout) -> AllocResult<NonNull<u8>> {
+        let pos = align_up(self.b_pos, layout.align());
+        let new_pos = pos.checked_add(layout.size()).ok_or(AllocError::NoMemory)?;
+        if new_pos > self.p_pos {
+            return Err(AllocError::NoMemory);
+        }
+        self.b_pos = new_pos;
+        self.count += 1;
+        self.peak_bytes = self.peak_bytes.max(self.used_bytes());
+        NonNull::new(pos as *mut u8).ok_or(AllocError::NoMemory)
+    }
+
+    fn dealloc(&mut self, _pos: NonNull<u8>, _layout: Layout) {
+        self.count = self.count.saturating_sub(1);
+        if self.count == 0 {
+            self.b_pos = self.start;
+        }
+    }
+
+    fn total_bytes(&self) -> usize {
+        self.end - self.start
+    }
 
-impl EarlyAllocator {
+    fn used_bytes(&self) -> usize {
+        self.b_pos - self.start
+    }
+
+    fn available_bytes(&self) -> usize {
+        self.p_pos - self.b_pos
+    }
 }
 
-impl BaseAllocator for EarlyAllocator {
+impl<const PAGE_SIZE: usize> PageAllocator for EarlyAllocator<PAGE_SIZE> {
+    const PAGE_SIZE: usize = PAGE_SIZE;
+
+    fn alloc_pages(&mut self, num_pages: usize, align_pow2: usize) -> AllocResult<usize> {
+        if align_pow2 < PAGE_SIZE || !align_pow2.is_power_of_two() {
+            return Err(AllocError::InvalidParam);
+        }
+        let size = num_pages
+            .checked_mul(PAGE_SIZE)
+            .ok_or(AllocError::InvalidParam)?;
+        let pos = align_down(
+            self.p_pos.checked_sub(size).ok_or(AllocError::NoMemory)?,
+            align_pow2,
+        );
+        if pos < self.b_pos {
+            return Err(AllocError::NoMemory);
+        }
+        self.p_pos = pos;
+        self.peak_pages = self.peak_pages.max(self.used_pages());
+        Ok(pos)
+    }
+
+    fn dealloc_pages(&mut self, _pos: usize, _num_pages: usize) {
+        // The pages area is never freed.
+    }
+
+    fn total_pages(&self) -> usize {
+        (self.end - self.start) / PAGE_SIZE
+    }
+
+    fn used_pages(&self) -> usize {
+        (self.end - self.p_pos) / PAGE_SIZE
+    }
+
+    fn available_pages(&self) -> usize {
+        (self.p_pos - self.b_pos) / PAGE_SIZE
+    }
+}
+
+const fn align_up(pos: usize, align: usize) -> usize {
+    (pos + align - 1) & !(align - 1)
 }
 
-impl ByteAllocator for EarlyAllocator {
+const fn align_down(pos: usize, align: usize) -> usize {
+    pos & !(align - 1)
 }
 
-impl PageAllocator for EarlyAllocator {
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_SIZE: usize = 0x1000;
+
+    fn new_allocator(arena: &mut [u8]) -> EarlyAllocator<PAGE_SIZE> {
+        let mut allocator = EarlyAllocator::new();
+        allocator.init(arena.as_mut_ptr() as usize, arena.len());
+        allocator
+    }
+
+    #[test]
+    fn dealloc_top_reuses_space() {
+        let mut arena = [0u8; 256];
+        let mut allocator = new_allocator(&mut arena);
+        let layout = Layout::from_size_align(16, 8).unwrap();
+
+        let first = allocator.alloc(layout).unwrap();
+        let second = allocator.alloc(layout).unwrap();
+        let used = allocator.used_bytes();
+
+        // Only the top allocation can be freed.
+        assert!(!allocator.try_dealloc_top(first, layout));
+        assert_eq!(allocator.used_bytes(), used);
+
+        assert!(allocator.try_dealloc_top(second, layout));
+        assert_eq!(allocator.used_bytes(), used - 16);
+        assert_eq!(allocator.alloc(layout).unwrap(), second);
+    }
+
+    #[test]
+    fn peak_outlives_free() {
+        let mut arena = [0u8; 4 * PAGE_SIZE];
+        let mut allocator = new_allocator(&mut arena);
+        let layout = Layout::from_size_align(100, 1).unwrap();
+
+        let first = allocator.alloc(layout).unwrap();
+        let second = allocator.alloc(layout).unwrap();
+        allocator.alloc_pages(1, PAGE_SIZE).unwrap();
+        let peak_bytes = allocator.used_bytes();
+        assert!(peak_bytes >= 200);
+
+        allocator.dealloc(first, layout);
+        allocator.dealloc(second, layout);
+        assert_eq!(allocator.used_bytes(), 0);
+        assert_eq!(allocator.peak_bytes_used(), peak_bytes);
+        assert_eq!(allocator.peak_pages_used(), 1);
+    }
 }
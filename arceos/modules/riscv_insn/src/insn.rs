@@ -0,0 +1,195 @@
+//! Decoding of trapped guest instructions.
+
+use crate::GprIndex;
+
+/// Returns the length in bytes of the instruction whose lowest bits are `insn`.
+///
+/// Compressed instructions (the C extension) are 2 bytes long, all the others
+/// are 4 bytes long.
+pub fn insn_len(insn: u32) -> usize {
+    if insn & 0b11 == 0b11 {
+        4
+    } else {
+        2
+    }
+}
+
+/// Returns the original instruction of the transformed instruction `htinst`
+/// and its length, or [`None`] if the hardware did not provide one.
+pub fn transformed_insn(htinst: usize) -> Option<(u32, usize)> {
+    let htinst = htinst as u32;
+    if htinst & 1 == 0 {
+        return None;
+    }
+    // A transformed instruction has bit 1 cleared if the original one was
+    // compressed. It is otherwise a standard 32-bit encoding.
+    let len = if htinst & 0b10 == 0 { 2 } else { 4 };
+    Some((htinst | 0b10, len))
+}
+
+/// A decoded guest load or store instruction.
+#[derive(Debug, Clone, Copy)]
+pub struct MemAccess {
+    /// Whether it is a store (`true`) or a load (`false`).
+    pub is_store: bool,
+    /// Access width in bytes.
+    pub width: usize,
+    /// Whether a loaded value is sign-extended.
+    pub signed: bool,
+    /// Destination register of a load, or source register of a store.
+    pub reg: GprIndex,
+}
+
+/// Decodes a load or store instruction, standard or compressed.
+///
+/// Returns [`None`] for any other instruction.
+pub fn decode_mem_access(insn: u32) -> Option<MemAccess> {
+    if insn_len(insn) == 4 {
+        let funct3 = (insn >> 12) & 0b111;
+        match insn & 0x7f {
+            // LOAD
+            0x03 => {
+                let (width, signed) = match funct3 {
+                    0b000 => (1, true),
+                    0b001 => (2, true),
+                    0b010 => (4, true),
+                    0b011 => (8, true),
+                    0b100 => (1, false),
+                    0b101 => (2, false),
+                    0b110 => (4, false),
+                    _ => return None,
+                };
+                Some(MemAccess {
+                    is_store: false,
+                    width,
+                    signed,
+                    reg: GprIndex::from_raw((insn >> 7) & 0x1f)?,
+                })
+            }
+            // STORE
+            0x23 if funct3 <= 0b011 => Some(MemAccess {
+                is_store: true,
+                width: 1 << funct3,
+                signed: false,
+                reg: GprIndex::from_raw((insn >> 20) & 0x1f)?,
+            }),
+            _ => None,
+        }
+    } else {
+        // Only quadrant 0 holds register-based loads/stores: c.lw, c.ld, c.sw, c.sd.
+        if insn & 0b11 != 0b00 {
+            return None;
+        }
+        let (is_store, width) = match (insn >> 13) & 0b111 {
+            0b010 => (false, 4),
+            0b011 => (false, 8),
+            0b110 => (true, 4),
+            0b111 => (true, 8),
+            _ => return None,
+        };
+        Some(MemAccess {
+            is_store,
+            width,
+            signed: true,
+            // rd' / rs2' encode x8-x15.
+            reg: GprIndex::from_raw(((insn >> 2) & 0b111) + 8)?,
+        })
+    }
+}
+
+/// A decoded guest instruction that only reads a CSR.
+#[derive(Debug, Clone, Copy)]
+pub struct CsrRead {
+    /// The CSR number.
+    pub csr: u16,
+    /// Destination register.
+    pub rd: GprIndex,
+}
+
+/// Decodes a CSR instruction that reads a CSR without writing it: `csrrs` or
+/// `csrrc` with `rs1 = x0` (as `csrr` is), or `csrrsi` or `csrrci` with a zero
+/// immediate.
+///
+/// Returns [`None`] for any other instruction.
+pub fn decode_csr_read(insn: u32) -> Option<CsrRead> {
+    // SYSTEM
+    if insn_len(insn) != 4 || insn & 0x7f != 0x73 {
+        return None;
+    }
+    let funct3 = (insn >> 12) & 0b111;
+    let rs1 = (insn >> 15) & 0x1f;
+    if !matches!(funct3, 0b010 | 0b011 | 0b110 | 0b111) || rs1 != 0 {
+        return None;
+    }
+    Some(CsrRead {
+        csr: (insn >> 20) as u16,
+        rd: GprIndex::from_raw((insn >> 7) & 0x1f)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insn_len() {
+        assert_eq!(insn_len(0x4188), 2); // c.lw a0, 0(a1)
+        assert_eq!(insn_len(0x0001), 2); // c.nop
+        assert_eq!(insn_len(0x0005a503), 4); // lw a0, 0(a1)
+        assert_eq!(insn_len(0x00000073), 4); // ecall
+    }
+
+    #[test]
+    fn test_transformed_insn() {
+        // A transformed c.lw has bit 1 cleared.
+        assert_eq!(transformed_insn(0x00002501), Some((0x00002503, 2)));
+        // A transformed lw keeps bit 1 set.
+        assert_eq!(transformed_insn(0x00002503), Some((0x00002503, 4)));
+        // No transformed instruction, it must be fetched from guest memory.
+        assert_eq!(transformed_insn(0), None);
+    }
+
+    #[test]
+    fn test_decode_compressed_mem_access() {
+        // c.lw a0, 0(a1)
+        let access = decode_mem_access(0x4188).unwrap();
+        assert!(!access.is_store);
+        assert_eq!((access.width, access.reg), (4, GprIndex::A0));
+        // c.sd a0, 8(a1)
+        let access = decode_mem_access(0xe588).unwrap();
+        assert!(access.is_store);
+        assert_eq!((access.width, access.reg), (8, GprIndex::A0));
+        // c.addi4spn is in quadrant 0 too, but is not a memory access.
+        assert!(decode_mem_access(0x0048).is_none());
+    }
+
+    #[test]
+    fn test_decode_mem_access() {
+        // lbu a0, 0(a1)
+        let access = decode_mem_access(0x0005c503).unwrap();
+        assert!(!access.is_store && !access.signed);
+        assert_eq!((access.width, access.reg), (1, GprIndex::A0));
+        // sd a0, 0(a1)
+        let access = decode_mem_access(0x00a5b023).unwrap();
+        assert!(access.is_store);
+        assert_eq!((access.width, access.reg), (8, GprIndex::A0));
+        // csrr a0, misa
+        assert!(decode_mem_access(0x30102573).is_none());
+    }
+
+    #[test]
+    fn test_decode_csr_read() {
+        // csrr a0, misa
+        let read = decode_csr_read(0x30102573).unwrap();
+        assert_eq!((read.csr, read.rd), (0x301, GprIndex::A0));
+        // csrrsi a1, marchid, 0
+        let read = decode_csr_read(0xf12065f3).unwrap();
+        assert_eq!((read.csr, read.rd), (0xf12, GprIndex::A1));
+        // csrw misa, a0 writes it
+        assert!(decode_csr_read(0x30151073).is_none());
+        // csrs misa, a0 sets bits
+        assert!(decode_csr_read(0x30152073).is_none());
+        // ecall
+        assert!(decode_csr_read(0x00000073).is_none());
+    }
+}
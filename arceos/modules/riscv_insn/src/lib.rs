@@ -0,0 +1,14 @@
+//! Decoding of the RISC-V guest instructions that trap into a hypervisor, and
+//! the general purpose register context they work on.
+//!
+//! It only works on instruction words and register indices, and never touches
+//! the hardware, so that it builds and is unit tested on the host, unlike the
+//! hypervisor itself.
+
+#![no_std]
+
+mod insn;
+mod regs;
+
+pub use insn::*;
+pub use regs::{GeneralPurposeRegisters, GprIndex};
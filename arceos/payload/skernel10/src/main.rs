@@ -0,0 +1,60 @@
+//! A guest printing a line with the legacy SBI console putchar, and another
+//! with the SBI Debug Console extension, then shutting down.
+//!
+//! It shuts down for a system failure if the Debug Console write does not
+//! report all the bytes as written. Run `simple_hv` with
+//! `ARGS=/sbin/skernel10`, it prints `Hello from SBI putchar!` and `Hello from
+//! SBI DBCN!`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// Length of the line printed through the Debug Console extension.
+const DBCN_LINE_LEN: usize = 21;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        // legacy console putchar, byte by byte
+        "la s1, 4f",
+        "1:",
+        "lbu a0, 0(s1)",
+        "beqz a0, 2f",
+        "li a7, 1",
+        "ecall",
+        "addi s1, s1, 1",
+        "j 1b",
+        "2:",
+        // SBI DBCN: console write
+        "mv a0, s2",
+        "la a1, 5f",
+        "li a2, 0",
+        "li a6, 0",
+        "li a7, 0x4442434e",
+        "ecall",
+        "li s3, 1",
+        "bnez a0, 3f",
+        "bne a1, s2, 3f",
+        "li s3, 0",
+        "3:",
+        // SBI SRST: shutdown, for a system failure unless DBCN wrote it all
+        "li a0, 0",
+        "mv a1, s3",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        "4:",
+        ".asciz \"Hello from SBI putchar!\\n\"",
+        "5:",
+        ".ascii \"Hello from SBI DBCN!\\n\"",
+        in("s2") DBCN_LINE_LEN,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,43 @@
+//! A guest calling an SBI extension that `simple_hv` does not implement, then
+//! shutting down.
+//!
+//! The call must fail with `SBI_ERR_NOT_SUPPORTED` and let the guest go on:
+//! the guest shuts down for a system failure if it gets another error. Run
+//! `simple_hv` with `ARGS=/sbin/skernel11`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// An extension ID of the experimental range, which nothing implements.
+const UNSUPPORTED_EID: usize = 0x0800_0000;
+/// `SBI_ERR_NOT_SUPPORTED`
+const ERR_NOT_SUPPORTED: isize = -2;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "mv a7, s1",
+        "li a6, 0",
+        "ecall",
+        "li a1, 1",
+        "bne a0, s2, 1f",
+        "li a1, 0",
+        "1:",
+        // SBI SRST: shutdown, for a system failure unless the call failed as
+        // expected
+        "li a0, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("s1") UNSUPPORTED_EID,
+        in("s2") ERR_NOT_SUPPORTED,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,39 @@
+//! A guest built as an ELF image, which stores to its read-only data segment.
+//!
+//! `simple_hv` must stop it on a guest page fault there, rather than map the
+//! page writable: the guest shuts down normally if the store goes through.
+//! Install the ELF file as `/sbin/skernel12`, and run `simple_hv` with
+//! `ARGS=/sbin/skernel12`, it exits on a `StoreGuestPageFault`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// Guest physical address of the read-only data segment, from `linker.ld`.
+const DATA_SEGMENT: usize = 0x8020_1000;
+
+#[used]
+#[link_section = ".rodata.magic"]
+static READ_ONLY: u64 = 0;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "li t1, 1",
+        "sd t1, 0(t0)",
+        // SBI SRST: shutdown, no reason
+        "li a0, 0",
+        "li a1, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("t0") DATA_SEGMENT,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
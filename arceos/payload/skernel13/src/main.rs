@@ -0,0 +1,110 @@
+//! A guest with two harts, each checking its hart id and that its registers
+//! are its own.
+//!
+//! Hart 0 starts hart 1 through the SBI HSM extension, and both set `s2` and
+//! `s3` to values of their own. Each makes SBI calls, so that the hypervisor
+//! switches between the two vCPUs, and checks that its values are still there.
+//! Hart 0 shuts down once hart 1 is done, for a system failure if any check
+//! failed. Run `simple_hv` with `ARGS=/sbin/skernel13`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// A page of guest RAM away from the image, where hart 1 reports to hart 0.
+/// It is mapped by the first write to it.
+const MAILBOX: usize = 0x8040_0000;
+/// Extension ID of SBI HSM.
+const EID_HSM: usize = 0x48534D;
+/// Passed to hart 1 in `a1` when it starts.
+const OPAQUE: usize = 0x5678;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        // Hart 0 boots with its hart id in a0.
+        "bnez a0, 9f",
+        "sd zero, 0(s4)",
+        "li s2, 0x1111",
+        "li s3, 0x2222",
+        // HSM hart_start(1, 2f, OPAQUE)
+        "li a0, 1",
+        "la a1, 2f",
+        "mv a2, s5",
+        "li a6, 0",
+        "mv a7, s6",
+        "ecall",
+        "bnez a0, 9f",
+        // Wait for hart 1 to be done, calling HSM hart_status(1) meanwhile.
+        "1:",
+        "li a0, 1",
+        "li a6, 2",
+        "mv a7, s6",
+        "ecall",
+        "ld t0, 0(s4)",
+        "beqz t0, 1b",
+        // Hart 1 reports 1 if all its checks passed.
+        "li t1, 1",
+        "bne t0, t1, 9f",
+        "li t1, 0x1111",
+        "bne s2, t1, 9f",
+        "li t1, 0x2222",
+        "bne s3, t1, 9f",
+        // SBI SRST: shutdown
+        "li a0, 0",
+        "li a1, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+
+        // Hart 1, with its hart id in a0 and OPAQUE in a1. Its other registers
+        // start out zeroed, so it sets up its own constants.
+        "2:",
+        "li s4, 0x80400000",
+        "li s5, 0x5678",
+        "li s6, 0x48534D",
+        "li t0, 2",
+        "li t1, 1",
+        "bne a0, t1, 3f",
+        "bne a1, s5, 3f",
+        "li s2, 0x3333",
+        "li s3, 0x4444",
+        // HSM hart_status(0)
+        "li a0, 0",
+        "li a6, 2",
+        "mv a7, s6",
+        "ecall",
+        "li t1, 0x3333",
+        "bne s2, t1, 3f",
+        "li t1, 0x4444",
+        "bne s3, t1, 3f",
+        "li t0, 1",
+        "3:",
+        "sd t0, 0(s4)",
+        // Keep calling HSM hart_status(0) until hart 0 shuts down.
+        "4:",
+        "li a0, 0",
+        "li a6, 2",
+        "mv a7, s6",
+        "ecall",
+        "j 4b",
+
+        // SBI SRST: shutdown for a system failure
+        "9:",
+        "li a0, 0",
+        "li a1, 1",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("s4") MAILBOX,
+        in("s5") OPAQUE,
+        in("s6") EID_HSM,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,105 @@
+//! A guest whose boot hart starts hart 1 through the SBI HSM extension, then
+//! waits for it to reach its entry.
+//!
+//! Hart 0 checks that hart 1 is stopped before the start and started after it,
+//! and that starting it again fails with `SBI_ERR_ALREADY_AVAILABLE`. Hart 1
+//! tells hart 0 it is there through a page of guest RAM, and hart 0 then shuts
+//! down, for a system failure if any check failed. Run `simple_hv` with
+//! `ARGS=/sbin/skernel14`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// A page of guest RAM away from the image, where hart 1 reports to hart 0.
+/// It is mapped by the first write to it.
+const MAILBOX: usize = 0x8040_0000;
+/// Extension ID of SBI HSM.
+const EID_HSM: usize = 0x48534D;
+/// `SBI_ERR_ALREADY_AVAILABLE`
+const ERR_ALREADY_AVAILABLE: isize = -6;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "sd zero, 0(s4)",
+        // HSM hart_status(1): stopped
+        "li a0, 1",
+        "li a6, 2",
+        "mv a7, s6",
+        "ecall",
+        "bnez a0, 9f",
+        "li t1, 1",
+        "bne a1, t1, 9f",
+        // HSM hart_start(1, 2f, 0)
+        "li a0, 1",
+        "la a1, 2f",
+        "li a2, 0",
+        "li a6, 0",
+        "mv a7, s6",
+        "ecall",
+        "bnez a0, 9f",
+        // HSM hart_status(1): started
+        "li a0, 1",
+        "li a6, 2",
+        "mv a7, s6",
+        "ecall",
+        "bnez a0, 9f",
+        "bnez a1, 9f",
+        // HSM hart_start(1, 2f, 0) again
+        "li a0, 1",
+        "la a1, 2f",
+        "li a2, 0",
+        "li a6, 0",
+        "mv a7, s6",
+        "ecall",
+        "bne a0, s5, 9f",
+        // Wait for hart 1, calling HSM hart_status(1) meanwhile.
+        "1:",
+        "li a0, 1",
+        "li a6, 2",
+        "mv a7, s6",
+        "ecall",
+        "ld t0, 0(s4)",
+        "beqz t0, 1b",
+        // SBI SRST: shutdown
+        "li a0, 0",
+        "li a1, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+
+        // Hart 1. Its registers start out zeroed but for a0 and a1, so it sets
+        // up its own constants.
+        "2:",
+        "li s4, 0x80400000",
+        "li s6, 0x48534D",
+        "li t0, 1",
+        "sd t0, 0(s4)",
+        // Keep calling HSM hart_status(0) until hart 0 shuts down.
+        "3:",
+        "li a0, 0",
+        "li a6, 2",
+        "mv a7, s6",
+        "ecall",
+        "j 3b",
+
+        // SBI SRST: shutdown for a system failure
+        "9:",
+        "li a0, 0",
+        "li a1, 1",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("s4") MAILBOX,
+        in("s5") ERR_ALREADY_AVAILABLE,
+        in("s6") EID_HSM,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
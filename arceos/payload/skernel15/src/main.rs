@@ -0,0 +1,29 @@
+//! A guest running `ebreak`.
+//!
+//! `simple_hv` does not delegate breakpoints to the guest, and has nothing to
+//! do with one: the guest must stop on a fatal fault. Run `simple_hv` with
+//! `ARGS=/sbin/skernel15`, it exits on a `Breakpoint`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "ebreak",
+        // SBI SRST: shutdown for a system failure, if the guest went on
+        "li a0, 0",
+        "li a1, 1",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
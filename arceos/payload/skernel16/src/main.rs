@@ -0,0 +1,33 @@
+//! A guest loading from an address outside its RAM, where no device is.
+//!
+//! The access cannot be emulated nor backed by a page: the guest must stop on
+//! a fatal fault. Run `simple_hv` with `ARGS=/sbin/skernel16`, it exits on a
+//! `LoadGuestPageFault`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// Neither guest RAM nor an emulated device.
+const UNBACKED_ADDR: usize = 0x2000_0000;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "ld t1, 0(t0)",
+        // SBI SRST: shutdown for a system failure, if the guest went on
+        "li a0, 0",
+        "li a1, 1",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("t0") UNBACKED_ADDR,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
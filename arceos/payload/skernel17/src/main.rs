@@ -0,0 +1,56 @@
+//! A guest with a trap handler, running an illegal instruction that
+//! `simple_hv` does not emulate.
+//!
+//! The hypervisor must hand the illegal instruction exception to the guest's
+//! handler. The handler checks `scause`, skips the instruction and returns to
+//! the guest, which then shuts down. It shuts down for a system failure if the
+//! handler sees another cause. Run `simple_hv` with `ARGS=/sbin/skernel17`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// `scause` of an illegal instruction exception.
+const ILLEGAL_INSTRUCTION: usize = 2;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "la t0, 2f",
+        "csrw stvec, t0",
+        // An M-mode CSR, out of reach of the guest.
+        "csrw mscratch, t0",
+        // SBI SRST: shutdown
+        "li a0, 0",
+        "li a1, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+
+        // The trap handler: skip the 4-byte instruction that trapped.
+        ".align 2",
+        "2:",
+        "csrr t0, scause",
+        "bne t0, s1, 9f",
+        "csrr t0, sepc",
+        "addi t0, t0, 4",
+        "csrw sepc, t0",
+        "sret",
+
+        // SBI SRST: shutdown for a system failure
+        "9:",
+        "li a0, 0",
+        "li a1, 1",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("s1") ILLEGAL_INSTRUCTION,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,48 @@
+//! A guest reading the machine-level ID CSRs `misa` and `mhartid`.
+//!
+//! They trap in VS-mode, and `simple_hv` emulates the reads. The guest checks
+//! that `misa` is RV64 IMAFDCSU and `mhartid` is 0 on its boot hart, and shuts
+//! down for a system failure otherwise. Run `simple_hv` with
+//! `ARGS=/sbin/skernel18`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// The `misa` of the guest: RV64 with the I, M, A, F, D, C, S and U
+/// extensions.
+const GUEST_MISA: usize = 2 << 62
+    | 1 << (b'I' - b'A')
+    | 1 << (b'M' - b'A')
+    | 1 << (b'A' - b'A')
+    | 1 << (b'F' - b'A')
+    | 1 << (b'D' - b'A')
+    | 1 << (b'C' - b'A')
+    | 1 << (b'S' - b'A')
+    | 1 << (b'U' - b'A');
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "li a1, 1",
+        "csrr t0, misa",
+        "bne t0, s1, 1f",
+        "csrr t0, mhartid",
+        "bnez t0, 1f",
+        "li a1, 0",
+        "1:",
+        // SBI SRST: shutdown, for a system failure if a check failed
+        "li a0, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("s1") GUEST_MISA,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,39 @@
+//! A guest checking that it runs at a non-default entry point.
+//!
+//! It reads its `pc` with `auipc`, and shuts down for a system failure unless
+//! it runs on the page of `ENTRY`. Run `simple_hv` with
+//! `ARGS="/sbin/skernel19 entry=0x80400000"`, so that the raw image is loaded
+//! and started there.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// Where the guest must be loaded, instead of the default 0x8020_0000.
+const ENTRY: usize = 0x8040_0000;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "li a1, 1",
+        "auipc t0, 0",
+        "srli t0, t0, 12",
+        "slli t0, t0, 12",
+        "bne t0, s1, 1f",
+        "li a1, 0",
+        "1:",
+        // SBI SRST: shutdown, for a system failure if the check failed
+        "li a0, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("s1") ENTRY,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
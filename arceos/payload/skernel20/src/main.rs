@@ -0,0 +1,33 @@
+//! A guest setting known register values, then hitting a fatal fault.
+//!
+//! Built with the `dump-guest` feature, `simple_hv` prints the guest registers
+//! when it stops the guest, and they must have these values. Run `simple_hv`
+//! with `APP_FEATURES=dump-guest ARGS=/sbin/skernel20`, it exits on a
+//! `Breakpoint`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "li a0, 0x11112222",
+        "li s2, 0xdeadbeef",
+        "li t6, 0x5a5a",
+        "ebreak",
+        // SBI SRST: shutdown for a system failure, if the guest went on
+        "li a0, 0",
+        "li a1, 1",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
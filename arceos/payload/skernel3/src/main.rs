@@ -0,0 +1,34 @@
+//! A guest writing to a page of its RAM that is not mapped yet, then reading
+//! the value back and passing it to the hypervisor at shutdown.
+//!
+//! `simple_hv` checks the value it gets in `a0` with the `check-shutdown-args`
+//! feature. Install the binary as `/sbin/skernel3`, and run `simple_hv` with
+//! `ARGS=/sbin/skernel3`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// A page of guest RAM away from the image, which the hypervisor maps on the
+/// first write.
+const FRESH_PAGE: usize = 0x8040_0000;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "li t1, 0x6688",
+        "sd t1, 0(t0)",
+        "ld a0, 0(t0)",
+        "li a1, 0x1234",
+        "li a7, 8",
+        "ecall",
+        in("t0") FRESH_PAGE,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,36 @@
+//! A guest spinning on the `time` CSR for about half a second, then shutting
+//! down.
+//!
+//! It never traps on its own, so `simple_hv` only gets the CPU back through its
+//! time slice timer. Build `simple_hv` with the `watchdog` feature and run it
+//! with `ARGS=/sbin/skernel4` to check that host tasks run meanwhile.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// How long to spin, in ticks of the 10 MHz `time` CSR of QEMU `virt`.
+const SPIN_TICKS: usize = 5_000_000;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "rdtime t1",
+        "add t1, t1, t0",
+        "1:",
+        "rdtime t2",
+        "bltu t2, t1, 1b",
+        "li a0, 0x6688",
+        "li a1, 0x1234",
+        "li a7, 8",
+        "ecall",
+        in("t0") SPIN_TICKS,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,49 @@
+//! A guest counting its boots in RAM, which asks for a warm reboot on its
+//! first boot and shuts down on the next one.
+//!
+//! Guest memory is kept across a reboot, so it shuts down for a system failure
+//! unless it re-entered at its entry point exactly once, which makes
+//! `simple_hv` exit with a failure. Run `simple_hv` with `ARGS=/sbin/skernel5`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// A page of guest RAM away from the image, which holds the boot count.
+const BOOT_COUNT: usize = 0x8040_0000;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "li t1, 1",
+        "amoadd.d t1, t1, (t0)",
+        "addi t1, t1, 1",
+        "li t2, 1",
+        "bne t1, t2, 1f",
+        // SBI SRST: warm reboot, no reason
+        "li a0, 2",
+        "li a1, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        "1:",
+        // SBI SRST: shutdown, for a system failure unless on the second boot
+        "li a0, 0",
+        "li a1, 0",
+        "li t2, 2",
+        "beq t1, t2, 2f",
+        "li a1, 1",
+        "2:",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("t0") BOOT_COUNT,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,6 @@
+fn main() {
+    // Lay the image out as `linker.ld` says, instead of the linker defaults.
+    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rustc-link-arg-bins=-T{dir}/linker.ld");
+    println!("cargo:rerun-if-changed=linker.ld");
+}
@@ -0,0 +1,44 @@
+//! A guest built as an ELF image with two loadable segments, as laid out by
+//! `linker.ld`: its code, and a read-only data page right after it.
+//!
+//! It checks that the data segment was loaded at its own guest physical
+//! address, then shuts down, for a system failure if it was not. Install the
+//! ELF file as `/sbin/skernel6`, and run `simple_hv` with `ARGS=/sbin/skernel6`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// Guest physical address of the data segment, from `linker.ld`.
+const DATA_SEGMENT: usize = 0x8020_1000;
+/// The value at the start of the data segment.
+const MAGIC: u64 = 0x5345_474d_454e_5432;
+
+#[used]
+#[link_section = ".rodata.magic"]
+static SEGMENT_MAGIC: u64 = MAGIC;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "ld t1, 0(t0)",
+        // SBI SRST: shutdown, for a system failure unless the magic is there
+        "li a0, 0",
+        "li a1, 0",
+        "beq t1, t2, 1f",
+        "li a1, 1",
+        "1:",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("t0") DATA_SEGMENT,
+        in("t2") MAGIC,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
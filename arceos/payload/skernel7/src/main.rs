@@ -0,0 +1,44 @@
+//! A guest printing a line through the UART of the QEMU `virt` machine, then
+//! shutting down.
+//!
+//! `simple_hv` emulates the UART: each byte is a store to its transmitter
+//! holding register, which traps and shows up on the host console. Run
+//! `simple_hv` with `ARGS=/sbin/skernel7`, it prints `Hello from the guest
+//! UART!`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// Guest physical address of the UART on the QEMU `virt` machine.
+const UART_BASE: usize = 0x1000_0000;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "la t1, 3f",
+        "1:",
+        "lbu t2, 0(t1)",
+        "beqz t2, 2f",
+        "sb t2, 0(t0)",
+        "addi t1, t1, 1",
+        "j 1b",
+        "2:",
+        // SBI SRST: shutdown, no reason
+        "li a0, 0",
+        "li a1, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        "3:",
+        ".asciz \"Hello from the guest UART!\\n\"",
+        in("t0") UART_BASE,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,42 @@
+//! A guest printing `C` through the emulated UART with a compressed store, then
+//! shutting down.
+//!
+//! The store is 2 bytes long, and the instruction after it must run once
+//! `simple_hv` resumes the guest: the guest shuts down for a system failure if
+//! it was skipped. Run `simple_hv` with `ARGS=/sbin/skernel8`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// Guest physical address of the UART on the QEMU `virt` machine.
+const UART_BASE: usize = 0x1000_0000;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "li a2, 0",
+        "li a0, 0x43",
+        "c.sw a0, 0(a1)",
+        "c.li a2, 1",
+        "li a0, 0x0a",
+        "c.sw a0, 0(a1)",
+        // SBI SRST: shutdown, for a system failure unless `c.li` ran
+        "li a0, 0",
+        "li a1, 1",
+        "beqz a2, 1f",
+        "li a1, 0",
+        "1:",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("a1") UART_BASE,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
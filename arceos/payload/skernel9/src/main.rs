@@ -0,0 +1,66 @@
+//! A guest setting a timer through the SBI TIME extension, and waiting for the
+//! timer interrupt in its own trap handler.
+//!
+//! It shuts down once it takes the interrupt, or for a system failure if it
+//! takes another trap, or no interrupt within a second. Run `simple_hv` with
+//! `ARGS=/sbin/skernel9`.
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// How long until the timer fires, in ticks of the 10 MHz `time` CSR of QEMU
+/// `virt`.
+const TIMER_TICKS: usize = 100_000;
+/// How long to wait for the interrupt before giving up.
+const TIMEOUT_TICKS: usize = 10_000_000;
+/// `scause` of a supervisor timer interrupt.
+const SCAUSE_TIMER: usize = 1 << 63 | 5;
+
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    core::arch::asm!(
+        "la t0, 2f",
+        "csrw stvec, t0",
+        // SBI TIME: set_timer(now + TIMER_TICKS)
+        "rdtime a0",
+        "add a0, a0, s1",
+        "li a6, 0",
+        "li a7, 0x54494d45",
+        "ecall",
+        // enable the timer interrupt
+        "li t0, 0x20",
+        "csrs sie, t0",
+        "csrsi sstatus, 0x2",
+        "rdtime t1",
+        "add s2, t1, s2",
+        "1:",
+        "rdtime t1",
+        "bltu t1, s2, 1b",
+        // timed out
+        "li a1, 1",
+        "j 3f",
+        ".align 2",
+        "2:",
+        "csrr t0, scause",
+        "li a1, 1",
+        "bne t0, s3, 3f",
+        "li a1, 0",
+        "3:",
+        // SBI SRST: shutdown, for a system failure unless the interrupt came
+        "li a0, 0",
+        "li a6, 0",
+        "li a7, 0x53525354",
+        "ecall",
+        in("s1") TIMER_TICKS,
+        in("s2") TIMEOUT_TICKS,
+        in("s3") SCAUSE_TIMER,
+        options(noreturn)
+    )
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,89 @@
+#!/bin/sh
+
+# Checks that the exit code of an app, after `process::exit` or
+# `process::abort`, reaches the exit status of QEMU. Then checks the exit
+# status of the `simple_hv` hypervisor with the test guests.
+#
+# The code goes through the SBI system reset, which only tells a failure from
+# a success, so QEMU exits with 1 for any nonzero code.
+
+expect_exit() {
+    make A=examples/exit_code ARCH=riscv64 ARGS="$1" build || exit 1
+    qemu-system-riscv64 -m 128M -machine virt -bios default -nographic \
+        -kernel examples/exit_code/exit_code_riscv64-qemu-virt.bin
+    status=$?
+    if [ "$status" -ne "$2" ]; then
+        echo "ARGS=\"$1\": expected exit status $2, got $status"
+        exit 1
+    fi
+}
+
+# Runs `simple_hv` with the guest `payload/<guest>`, and checks the exit status
+# of QEMU, and that the output has the line given as a third argument if any.
+# A fourth argument is passed to `simple_hv` after the image, to set the guest
+# layout, and a fifth one is the features to build `simple_hv` with.
+#
+# A normal guest shutdown exits with 0, a fatal guest fault with 1. The guest
+# is written into disk.img with update_disk.sh, which needs sudo.
+expect_hv_exit() {
+    [ -f disk.img ] || make disk_img || exit 1
+    make -C payload/$1 || exit 1
+    ./update_disk.sh payload/$1/$1
+    make A=exercises/simple_hv ARCH=riscv64 ARGS="/sbin/$1 $4" APP_FEATURES="$5" build || exit 1
+    timeout 60 qemu-system-riscv64 -m 128M -machine virt -bios default -nographic \
+        -kernel exercises/simple_hv/simple_hv_riscv64-qemu-virt.bin \
+        -device virtio-blk-pci,drive=disk0 \
+        -drive id=disk0,if=none,format=raw,file=disk.img > hv_output.txt 2>&1
+    status=$?
+    cat hv_output.txt
+    if [ "$status" -ne "$2" ]; then
+        echo "simple_hv with $1: expected exit status $2, got $status"
+        exit 1
+    fi
+    # A host panic exits with 1 as well, e.g. when the host CSRs are not
+    # restored on a guest exit: it must not pass for a guest fault.
+    if grep -qF "panicked at" hv_output.txt; then
+        echo "simple_hv with $1: the host panicked"
+        exit 1
+    fi
+    if [ -n "$3" ]; then
+        expect_hv_output "$3"
+    fi
+}
+
+# Checks that the output of the last `simple_hv` run has the given line.
+expect_hv_output() {
+    if ! grep -qF "$1" hv_output.txt; then
+        echo "simple_hv: no \"$1\" in the output"
+        exit 1
+    fi
+}
+
+expect_exit 0 0
+expect_exit 3 1
+expect_exit -1 1
+expect_exit abort 1
+
+expect_hv_exit skernel3 0 "Shutdown vm normally!"
+expect_hv_exit skernel4 0
+expect_hv_exit skernel5 0
+expect_hv_exit skernel6 0
+expect_hv_exit skernel7 0 "Hello from the guest UART!"
+expect_hv_exit skernel8 0
+expect_hv_exit skernel9 0
+expect_hv_exit skernel10 0 "Hello from SBI DBCN!"
+expect_hv_exit skernel11 0
+expect_hv_exit skernel12 1 "Guest fault: Exception(StoreGuestPageFault)"
+expect_hv_exit skernel13 0
+expect_hv_exit skernel14 0
+expect_hv_exit skernel15 1 "Guest fault: Exception(Breakpoint)"
+expect_hv_exit skernel16 1 "Guest fault: Exception(LoadGuestPageFault)"
+expect_hv_exit skernel17 0
+expect_hv_exit skernel18 0
+expect_hv_exit skernel19 0 "" entry=0x80400000
+expect_hv_exit skernel20 1 "Guest state at Exception(Breakpoint):" "" dump-guest
+expect_hv_output "  a0: 0x0000000011112222"
+expect_hv_output "  s2: 0x00000000deadbeef"
+expect_hv_output "  t6: 0x0000000000005a5a"
+
+echo "test_exit_code.sh OK!"
@@ -0,0 +1,1252 @@
+//! A hash map implemented with open addressing and linear probing.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+use core::{fmt, mem, slice};
+
+/// Number of buckets of a new map.
+const INITIAL_CAPACITY: usize = 16;
+/// Default fraction of the buckets that may be in use (occupied or
+/// tombstones) before the table grows.
+const LOAD_FACTOR: f64 = 0.7;
+
+#[derive(Default)]
+enum Bucket<K, V> {
+    #[default]
+    Empty,
+    /// A removed entry. Probing goes on past it.
+    Tombstone,
+    Occupied(K, V),
+}
+
+/// A multiplicative hasher (`h = h * 31 + byte`) started from a seed.
+struct SeededHasher(u64);
+
+impl Hasher for SeededHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self.0.wrapping_mul(31).wrapping_add(b as u64);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        // Mix the high bits down, as the bucket index only takes the low bits.
+        let h = self.0;
+        h ^ (h >> 32) ^ (h >> 16)
+    }
+}
+
+/// A hash map implemented with open addressing and linear probing.
+///
+/// The hasher is seeded by [`ax_random`] when the map is created, so the
+/// iteration order is unspecified and differs between maps. Maps created with
+/// [`with_seed`] hash deterministically instead, which makes tests
+/// reproducible. A map keeps its seed for its whole lifetime, including
+/// across resizes.
+///
+/// [`with_seed`]: HashMap::with_seed
+/// [`ax_random`]: arceos_api::sys::ax_random
+pub struct HashMap<K, V> {
+    buckets: Vec<Bucket<K, V>>,
+    len: usize,
+    tombstones: usize,
+    seed: u128,
+    load_factor: f64,
+}
+
+impl<K, V> HashMap<K, V> {
+    /// Creates an empty `HashMap`.
+    pub fn new() -> Self {
+        Self::with_capacity(INITIAL_CAPACITY)
+    }
+
+    /// Creates an empty `HashMap` with at least `capacity` buckets.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_seed(capacity, arceos_api::sys::ax_random())
+    }
+
+    /// Creates an empty `HashMap` whose hasher is seeded by `seed`.
+    ///
+    /// Maps with the same seed given the same operations hash, and iterate, in
+    /// the same order.
+    pub fn with_seed(seed: u128) -> Self {
+        Self::with_capacity_and_seed(INITIAL_CAPACITY, seed)
+    }
+
+    /// Creates an empty `HashMap` with at least `capacity` buckets, whose
+    /// hasher is seeded by `seed`.
+    pub fn with_capacity_and_seed(capacity: usize, seed: u128) -> Self {
+        Self::with_capacity_seed_and_load_factor(capacity, seed, LOAD_FACTOR)
+    }
+
+    /// Creates an empty `HashMap` that grows once `factor` of its buckets are
+    /// in use, instead of the default 0.7.
+    ///
+    /// A lower factor keeps probe sequences shorter at the cost of memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not within `(0.0, 1.0)`.
+    pub fn with_load_factor(factor: f64) -> Self {
+        Self::with_capacity_seed_and_load_factor(
+            INITIAL_CAPACITY,
+            arceos_api::sys::ax_random(),
+            factor,
+        )
+    }
+
+    /// Creates an empty `HashMap` with at least `capacity` buckets, whose
+    /// hasher is seeded by `seed`, and that grows once `factor` of its buckets
+    /// are in use.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not within `(0.0, 1.0)`.
+    pub fn with_capacity_seed_and_load_factor(capacity: usize, seed: u128, factor: f64) -> Self {
+        assert!(
+            factor > 0.0 && factor < 1.0,
+            "load factor {} is not within (0.0, 1.0)",
+            factor
+        );
+        Self {
+            buckets: empty_buckets(capacity.max(INITIAL_CAPACITY).next_power_of_two()),
+            len: 0,
+            tombstones: 0,
+            seed,
+            load_factor: factor,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of buckets of the map.
+    pub fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Removes all the elements, keeping the allocated buckets.
+    pub fn clear(&mut self) {
+        self.buckets.fill_with(Default::default);
+        self.len = 0;
+        self.tombstones = 0;
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. Keeps the
+    /// allocated buckets for reuse.
+    ///
+    /// If the returned iterator is dropped before being fully consumed, it
+    /// drops the remaining key-value pairs. If it is leaked instead (e.g. with
+    /// [`mem::forget`]), the map keeps the pairs not yielded yet.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain { map: self, next: 0 }
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.buckets.iter(),
+            remaining: self.len,
+        }
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order, with
+    /// mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.buckets.iter_mut(),
+            remaining: self.len,
+        }
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// An iterator visiting all values in arbitrary order.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Creates a raw immutable entry builder, to look up an entry by a hash
+    /// computed with [`hash_one`] and a key comparator.
+    ///
+    /// [`hash_one`]: HashMap::hash_one
+    pub fn raw_entry(&self) -> RawEntryBuilder<'_, K, V> {
+        RawEntryBuilder { map: self }
+    }
+
+    /// Creates a raw entry builder, to look up an entry by a hash computed
+    /// with [`hash_one`] and a key comparator, and then to insert or modify
+    /// it without hashing the key again.
+    ///
+    /// [`hash_one`]: HashMap::hash_one
+    pub fn raw_entry_mut(&mut self) -> RawEntryBuilderMut<'_, K, V> {
+        RawEntryBuilderMut { map: self }
+    }
+
+    /// Calculates the hash of `key` with the hasher of this map, for the raw
+    /// entry API.
+    ///
+    /// The hash of a key does not change during the lifetime of the map.
+    pub fn hash_one<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash(key)
+    }
+
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        let mut hasher = SeededHasher(self.seed as u64 ^ (self.seed >> 64) as u64);
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the index of the bucket holding the key with `hash` for which
+    /// `is_match` returns `true`.
+    fn find_hashed(&self, hash: u64, mut is_match: impl FnMut(&K) -> bool) -> Option<usize> {
+        let mask = self.buckets.len() - 1;
+        let mut idx = hash as usize & mask;
+        let mut probes = 0;
+        loop {
+            probes += 1;
+            debug_assert!(probes <= self.buckets.len(), "no empty bucket in the table");
+            match &self.buckets[idx] {
+                Bucket::Empty => return None,
+                Bucket::Occupied(k, _) if is_match(k) => return Some(idx),
+                _ => {}
+            }
+            idx = (idx + 1) & mask;
+        }
+    }
+
+    /// Returns the index of the first free bucket, empty or tombstone, along
+    /// the probe sequence of `hash`.
+    fn free_bucket(&self, hash: u64) -> usize {
+        let mask = self.buckets.len() - 1;
+        let mut idx = hash as usize & mask;
+        while let Bucket::Occupied(..) = self.buckets[idx] {
+            idx = (idx + 1) & mask;
+        }
+        idx
+    }
+}
+
+impl<K: Hash + Eq, V> HashMap<K, V> {
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map already had this key present, the value is updated, and the
+    /// old value is returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hash(&key);
+        if let Some(idx) = self.find_hashed(hash, |k| *k == key) {
+            match &mut self.buckets[idx] {
+                Bucket::Occupied(_, v) => return Some(mem::replace(v, value)),
+                _ => unreachable!(),
+            }
+        }
+        self.insert_new(hash, key, value);
+        None
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &self.buckets[self.find(key)?] {
+            Bucket::Occupied(_, v) => Some(v),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    ///
+    /// The returned key is the one stored in the map, which may be a
+    /// different instance than `key`.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &self.buckets[self.find(key)?] {
+            Bucket::Occupied(k, v) => Some((k, v)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        match &mut self.buckets[idx] {
+            Bucket::Occupied(_, v) => Some(v),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        match mem::replace(&mut self.buckets[idx], Bucket::Tombstone) {
+            Bucket::Occupied(_, v) => {
+                self.len -= 1;
+                self.tombstones += 1;
+                Some(v)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Shrinks the table to the smallest number of buckets that holds the
+    /// current entries within the load factor, dropping all tombstones.
+    pub fn shrink_to_fit(&mut self) {
+        let mut new_cap = INITIAL_CAPACITY;
+        while self.len >= (new_cap as f64 * self.load_factor) as usize {
+            new_cap *= 2;
+        }
+        self.resize(new_cap);
+    }
+
+    /// Returns the average and the maximum probe length of the entries: how
+    /// many buckets past the one its key hashes to each entry is stored.
+    ///
+    /// This is meant for diagnosing keys that hash poorly. The average is
+    /// 0.0 for an empty map.
+    pub fn probe_stats(&self) -> (f64, usize) {
+        let mask = self.buckets.len() - 1;
+        let (mut total, mut max) = (0, 0);
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            if let Bucket::Occupied(k, _) = bucket {
+                let ideal = self.hash(k) as usize & mask;
+                let probe = idx.wrapping_sub(ideal) & mask;
+                total += probe;
+                max = max.max(probe);
+            }
+        }
+        let avg = if self.len == 0 {
+            0.0
+        } else {
+            total as f64 / self.len as f64
+        };
+        (avg, max)
+    }
+
+    /// Returns the index of the bucket holding `key`.
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_hashed(self.hash(key), |k| k.borrow() == key)
+    }
+
+    /// Inserts an entry whose key, which has `hash`, is not in the map yet.
+    /// Returns the index of its bucket.
+    ///
+    /// Only this path can grow the table, so overwriting the value of an
+    /// existing key never rehashes.
+    fn insert_new(&mut self, hash: u64, key: K, value: V) -> usize {
+        self.reserve_one();
+
+        let idx = self.free_bucket(hash);
+        if let Bucket::Tombstone = self.buckets[idx] {
+            self.tombstones -= 1;
+        }
+        self.buckets[idx] = Bucket::Occupied(key, value);
+        self.len += 1;
+        idx
+    }
+
+    /// Makes room for one more entry, so that the table keeps empty buckets
+    /// for the probing to stop at.
+    ///
+    /// The occupied buckets and tombstones stay below the threshold, which is
+    /// less than the number of buckets as the load factor is below 1. So there
+    /// is always an empty bucket, and probing for an absent key terminates.
+    fn reserve_one(&mut self) {
+        let threshold = (self.buckets.len() as f64 * self.load_factor) as usize;
+        if self.len + self.tombstones < threshold {
+            return;
+        }
+        // If most of the used buckets are tombstones, rehashing at the same
+        // size is enough to get rid of them.
+        let new_cap = if self.len + 1 < threshold / 2 {
+            self.buckets.len()
+        } else {
+            self.buckets.len() * 2
+        };
+        self.resize(new_cap);
+    }
+
+    /// Rehashes all the entries into `new_cap` buckets, dropping tombstones.
+    fn resize(&mut self, new_cap: usize) {
+        let old = mem::replace(&mut self.buckets, empty_buckets(new_cap));
+        self.tombstones = 0;
+
+        let mask = new_cap - 1;
+        for bucket in old {
+            if let Bucket::Occupied(k, v) = bucket {
+                let mut idx = self.hash(&k) as usize & mask;
+                while !matches!(self.buckets[idx], Bucket::Empty) {
+                    idx = (idx + 1) & mask;
+                }
+                self.buckets[idx] = Bucket::Occupied(k, v);
+            }
+        }
+    }
+}
+
+fn empty_buckets<K, V>(n: usize) -> Vec<Bucket<K, V>> {
+    let mut buckets = Vec::with_capacity(n);
+    buckets.resize_with(n, Default::default);
+    buckets
+}
+
+impl<K, V> Default for HashMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for HashMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V> Extend<(K, V)> for HashMap<K, V> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for HashMap<K, V> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> IntoIterator for HashMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Creates a consuming iterator, moving each key-value pair out of the map
+    /// in arbitrary order.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            remaining: self.len,
+            inner: self.buckets.into_iter(),
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a HashMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut HashMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the entries of a [`HashMap`].
+pub struct Iter<'a, K, V> {
+    inner: slice::Iter<'a, Bucket<K, V>>,
+    /// Number of entries not yielded yet.
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.inner.find_map(|bucket| match bucket {
+            Bucket::Occupied(k, v) => Some((k, v)),
+            _ => None,
+        })?;
+        self.remaining -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// A mutable iterator over the entries of a [`HashMap`].
+pub struct IterMut<'a, K, V> {
+    inner: slice::IterMut<'a, Bucket<K, V>>,
+    /// Number of entries not yielded yet.
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.inner.find_map(|bucket| match bucket {
+            Bucket::Occupied(k, v) => Some((&*k, v)),
+            _ => None,
+        })?;
+        self.remaining -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// An owning iterator over the entries of a [`HashMap`].
+pub struct IntoIter<K, V> {
+    inner: vec::IntoIter<Bucket<K, V>>,
+    /// Number of entries not yielded yet.
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.inner.find_map(|bucket| match bucket {
+            Bucket::Occupied(k, v) => Some((k, v)),
+            _ => None,
+        })?;
+        self.remaining -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+/// A draining iterator over the entries of a [`HashMap`].
+///
+/// Every bucket it goes past is emptied.
+pub struct Drain<'a, K, V> {
+    /// The map, which stays consistent as entries are yielded: each one is
+    /// removed as by [`HashMap::remove`].
+    map: &'a mut HashMap<K, V>,
+    /// Index of the next bucket to look at.
+    next: usize,
+}
+
+impl<K, V> Iterator for Drain<'_, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(bucket) = self.map.buckets.get_mut(self.next) {
+            self.next += 1;
+            if !matches!(bucket, Bucket::Occupied(..)) {
+                continue;
+            }
+            match mem::replace(bucket, Bucket::Tombstone) {
+                Bucket::Occupied(k, v) => {
+                    self.map.len -= 1;
+                    self.map.tombstones += 1;
+                    return Some((k, v));
+                }
+                _ => unreachable!(),
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.len, Some(self.map.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {}
+
+impl<K, V> Drop for Drain<'_, K, V> {
+    fn drop(&mut self) {
+        self.map.clear();
+    }
+}
+
+/// An iterator over the keys of a [`HashMap`].
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+/// An iterator over the values of a [`HashMap`].
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+/// A builder for looking up an entry of a [`HashMap`] by its hash.
+///
+/// Created by [`HashMap::raw_entry`].
+pub struct RawEntryBuilder<'a, K, V> {
+    map: &'a HashMap<K, V>,
+}
+
+// The `from_*` names follow the raw entry API of hashbrown.
+#[allow(clippy::wrong_self_convention)]
+impl<'a, K, V> RawEntryBuilder<'a, K, V> {
+    /// Looks up the entry whose key has `hash` and matches `is_match`.
+    pub fn from_hash<F>(self, hash: u64, is_match: F) -> Option<(&'a K, &'a V)>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        match &self.map.buckets[self.map.find_hashed(hash, is_match)?] {
+            Bucket::Occupied(k, v) => Some((k, v)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Looks up the entry of `key`, which has `hash`.
+    pub fn from_key_hashed_nocheck<Q>(self, hash: u64, key: &Q) -> Option<(&'a K, &'a V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.from_hash(hash, |k| k.borrow() == key)
+    }
+}
+
+/// A builder for looking up an entry of a [`HashMap`] by its hash, to insert
+/// or modify it.
+///
+/// Created by [`HashMap::raw_entry_mut`].
+pub struct RawEntryBuilderMut<'a, K, V> {
+    map: &'a mut HashMap<K, V>,
+}
+
+// The `from_*` names follow the raw entry API of hashbrown.
+#[allow(clippy::wrong_self_convention)]
+impl<'a, K, V> RawEntryBuilderMut<'a, K, V> {
+    /// Looks up the entry whose key has `hash` and matches `is_match`.
+    pub fn from_hash<F>(self, hash: u64, is_match: F) -> RawEntryMut<'a, K, V>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        match self.map.find_hashed(hash, is_match) {
+            Some(idx) => RawEntryMut::Occupied(RawOccupiedEntryMut { map: self.map, idx }),
+            None => RawEntryMut::Vacant(RawVacantEntryMut {
+                map: self.map,
+                hash,
+            }),
+        }
+    }
+
+    /// Looks up the entry of `key`, which has `hash`.
+    pub fn from_key_hashed_nocheck<Q>(self, hash: u64, key: &Q) -> RawEntryMut<'a, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.from_hash(hash, |k| k.borrow() == key)
+    }
+}
+
+/// A raw entry of a [`HashMap`], which is either occupied or vacant.
+///
+/// Created by [`RawEntryBuilderMut::from_hash`].
+pub enum RawEntryMut<'a, K, V> {
+    /// An occupied entry.
+    Occupied(RawOccupiedEntryMut<'a, K, V>),
+    /// A vacant entry.
+    Vacant(RawVacantEntryMut<'a, K, V>),
+}
+
+impl<'a, K: Hash + Eq, V> RawEntryMut<'a, K, V> {
+    /// Returns references to the key and the value of the entry, inserting
+    /// `key` and `value` first if it is vacant.
+    pub fn or_insert(self, key: K, value: V) -> (&'a mut K, &'a mut V) {
+        self.or_insert_with(|| (key, value))
+    }
+
+    /// Returns references to the key and the value of the entry, inserting the
+    /// result of `default` first if it is vacant.
+    pub fn or_insert_with<F>(self, default: F) -> (&'a mut K, &'a mut V)
+    where
+        F: FnOnce() -> (K, V),
+    {
+        match self {
+            RawEntryMut::Occupied(entry) => entry.into_key_value(),
+            RawEntryMut::Vacant(entry) => {
+                let (key, value) = default();
+                entry.insert(key, value)
+            }
+        }
+    }
+}
+
+/// An occupied raw entry of a [`HashMap`].
+pub struct RawOccupiedEntryMut<'a, K, V> {
+    map: &'a mut HashMap<K, V>,
+    idx: usize,
+}
+
+impl<'a, K, V> RawOccupiedEntryMut<'a, K, V> {
+    /// Returns a reference to the key of the entry.
+    pub fn key(&self) -> &K {
+        self.get_key_value().0
+    }
+
+    /// Returns a reference to the value of the entry.
+    pub fn get(&self) -> &V {
+        self.get_key_value().1
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        match &mut self.map.buckets[self.idx] {
+            Bucket::Occupied(_, v) => v,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Converts the entry into a mutable reference to its value, with the
+    /// lifetime of the map.
+    pub fn into_mut(self) -> &'a mut V {
+        self.into_key_value().1
+    }
+
+    /// Converts the entry into mutable references to its key and its value,
+    /// with the lifetime of the map.
+    ///
+    /// The key must not be modified in a way that changes its hash or
+    /// equality.
+    pub fn into_key_value(self) -> (&'a mut K, &'a mut V) {
+        match &mut self.map.buckets[self.idx] {
+            Bucket::Occupied(k, v) => (k, v),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the value of the entry, and returns the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map, and returns its key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        match mem::replace(&mut self.map.buckets[self.idx], Bucket::Tombstone) {
+            Bucket::Occupied(k, v) => {
+                self.map.len -= 1;
+                self.map.tombstones += 1;
+                (k, v)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Removes the entry from the map, and returns its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    fn get_key_value(&self) -> (&K, &V) {
+        match &self.map.buckets[self.idx] {
+            Bucket::Occupied(k, v) => (k, v),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// A vacant raw entry of a [`HashMap`].
+pub struct RawVacantEntryMut<'a, K, V> {
+    map: &'a mut HashMap<K, V>,
+    hash: u64,
+}
+
+impl<'a, K: Hash + Eq, V> RawVacantEntryMut<'a, K, V> {
+    /// Inserts `key` and `value` into the map, and returns references to them.
+    ///
+    /// `key` must have the hash the entry was looked up with. It is not
+    /// hashed again.
+    pub fn insert(self, key: K, value: V) -> (&'a mut K, &'a mut V) {
+        let idx = self.map.insert_new(self.hash, key, value);
+        match &mut self.map.buckets[idx] {
+            Bucket::Occupied(k, v) => (k, v),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    /// A value which counts how many times it was dropped.
+    struct DropCounter<'a>(&'a Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map = HashMap::with_seed(1);
+        assert!(map.is_empty());
+        assert_eq!(map.insert(1, "one"), None);
+        assert_eq!(map.insert(2, "two"), None);
+        assert_eq!(map.insert(1, "uno"), Some("one"));
+        assert_eq!(map.len(), 2);
+
+        assert_eq!(map.get(&1), Some(&"uno"));
+        assert_eq!(map.get(&3), None);
+        *map.get_mut(&2).unwrap() = "dos";
+        assert_eq!(map.remove(&2), Some("dos"));
+        assert_eq!(map.remove(&2), None);
+        assert!(!map.contains_key(&2));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn tombstone_reuse() {
+        let mut map = HashMap::with_seed(1);
+        map.insert(7, 0);
+        map.remove(&7);
+        assert_eq!(map.tombstones, 1);
+
+        // The key probes to the same place, so it goes into the tombstone.
+        map.insert(7, 1);
+        assert_eq!(map.tombstones, 0);
+        assert_eq!(map.get(&7), Some(&1));
+
+        // Lookups go on past tombstones.
+        for i in 0..8 {
+            map.insert(i, i);
+        }
+        for i in 0..4 {
+            map.remove(&i);
+        }
+        for i in 4..8 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+    }
+
+    #[test]
+    fn resize() {
+        let mut map = HashMap::with_seed(1);
+        for i in 0..1000 {
+            map.insert(i, i * 2);
+        }
+        assert_eq!(map.len(), 1000);
+        assert!(map.capacity() >= 1000);
+        for i in 0..1000 {
+            assert_eq!(map.get(&i), Some(&(i * 2)));
+        }
+        assert_eq!(map.get(&1000), None);
+    }
+
+    #[test]
+    fn get_key_value_returns_stored_key() {
+        let mut map = HashMap::with_seed(1);
+        let stored = String::from("key");
+        let stored_ptr = stored.as_ptr();
+        map.insert(stored, 1);
+
+        let query = String::from("key");
+        let (key, value) = map.get_key_value(&query).unwrap();
+        assert_eq!(*value, 1);
+        assert_eq!(key.as_ptr(), stored_ptr);
+        assert_ne!(key.as_ptr(), query.as_ptr());
+        assert_eq!(map.get_key_value("missing"), None);
+    }
+
+    #[test]
+    fn drop_values() {
+        let drops = Cell::new(0);
+        let mut map = HashMap::with_seed(1);
+        for i in 0..100 {
+            map.insert(i, DropCounter(&drops));
+        }
+        assert_eq!(drops.get(), 0, "resizing must not drop values");
+
+        drop(map.remove(&0));
+        map.insert(1, DropCounter(&drops));
+        assert_eq!(drops.get(), 2);
+
+        let len = map.len();
+        drop(map);
+        assert_eq!(drops.get(), 2 + len);
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut map = HashMap::with_seed(1);
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        for i in 10..1000 {
+            map.remove(&i);
+        }
+        let capacity = map.capacity();
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), INITIAL_CAPACITY);
+        assert!(map.capacity() * 16 <= capacity);
+        assert_eq!(map.tombstones, 0);
+        assert_eq!(map.len(), 10);
+        for i in 0..10 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut map = HashMap::with_seed(1);
+        for i in 0..20 {
+            map.insert(i, 0);
+        }
+        // Leave tombstones behind for the iterator to skip.
+        for i in 0..20 {
+            if i % 3 == 0 {
+                map.remove(&i);
+            }
+        }
+        for (k, v) in map.iter_mut() {
+            *v = k * 10;
+        }
+        assert_eq!(map.iter_mut().count(), map.len());
+        for i in 0..20 {
+            let expected = if i % 3 == 0 { None } else { Some(&(i * 10)) };
+            assert_eq!(map.get(&i), expected);
+        }
+    }
+
+    #[test]
+    fn probe_stats() {
+        let mut map = HashMap::with_seed(1);
+        assert_eq!(map.probe_stats(), (0.0, 0));
+
+        // Adversarial keys which all hash to bucket 0 of the initial table.
+        let mask = map.capacity() - 1;
+        let keys: Vec<u32> = (0..)
+            .filter(|k| map.hash_one(k) as usize & mask == 0)
+            .take(4)
+            .collect();
+        for &k in &keys {
+            map.insert(k, ());
+        }
+        assert_eq!(map.capacity(), INITIAL_CAPACITY);
+        // They sit in buckets 0 to 3, 0 + 1 + 2 + 3 buckets past their own.
+        assert_eq!(map.probe_stats(), (1.5, 3));
+    }
+
+    #[test]
+    fn same_seed_same_order() {
+        let mut a = HashMap::with_seed(42);
+        let mut b = HashMap::with_capacity_and_seed(INITIAL_CAPACITY, 42);
+        for i in 0..100 {
+            a.insert(i, i);
+            b.insert(i, i);
+        }
+        assert!(a.iter().eq(b.iter()));
+    }
+
+    #[test]
+    fn custom_load_factor() {
+        let mut low = HashMap::with_capacity_seed_and_load_factor(0, 1, 0.25);
+        let mut default = HashMap::with_seed(1);
+        for i in 0..4 {
+            low.insert(i, i);
+            default.insert(i, i);
+        }
+        assert_eq!(low.capacity(), INITIAL_CAPACITY);
+
+        // A quarter of 16 buckets are in use, so the next insertion grows.
+        low.insert(4, 4);
+        default.insert(4, 4);
+        assert_eq!(low.capacity(), INITIAL_CAPACITY * 2);
+        assert_eq!(default.capacity(), INITIAL_CAPACITY);
+        for i in 0..5 {
+            assert_eq!(low.get(&i), Some(&i));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "not within (0.0, 1.0)")]
+    fn load_factor_one() {
+        HashMap::<u32, u32>::with_load_factor(1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "not within (0.0, 1.0)")]
+    fn load_factor_zero() {
+        HashMap::<u32, u32>::with_capacity_seed_and_load_factor(0, 1, 0.0);
+    }
+
+    #[test]
+    fn exact_size_iter() {
+        let mut map = HashMap::with_seed(1);
+        for i in 0..10 {
+            map.insert(i, i);
+        }
+        map.remove(&0);
+
+        let mut iter = map.iter();
+        assert_eq!(iter.len(), map.len());
+        for remaining in (0..map.len()).rev() {
+            iter.next().unwrap();
+            assert_eq!(iter.len(), remaining);
+        }
+        assert!(iter.next().is_none());
+        assert_eq!(iter.len(), 0);
+        assert_eq!(map.keys().len(), 9);
+        assert_eq!(map.values().size_hint(), (9, Some(9)));
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut map = HashMap::with_seed(1);
+        for i in 0..10 {
+            map.insert(i, i.to_string());
+        }
+        map.remove(&5);
+
+        let iter = map.into_iter();
+        assert_eq!(iter.len(), 9);
+        let mut entries: Vec<(u32, String)> = iter.collect();
+        entries.sort();
+        let expected: Vec<_> = (0..10)
+            .filter(|&i| i != 5)
+            .map(|i| (i, i.to_string()))
+            .collect();
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    fn drain() {
+        let mut map = HashMap::with_seed(1);
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+        let capacity = map.capacity();
+
+        let mut drained: Vec<_> = map.drain().collect();
+        drained.sort();
+        assert!(drained.into_iter().eq((0..100).map(|i| (i, i))));
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+        assert_eq!(map.capacity(), capacity);
+
+        // The map is usable again.
+        map.insert(1, 1);
+        assert_eq!(map.get(&1), Some(&1));
+    }
+
+    #[test]
+    fn drain_forgotten() {
+        let mut map = HashMap::with_seed(1);
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+
+        let mut drain = map.drain();
+        let drained: Vec<_> = drain.by_ref().take(10).map(|(k, _)| k).collect();
+        assert_eq!(drain.len(), 90);
+        mem::forget(drain);
+
+        // The pairs not yielded are still in the map, and only those.
+        assert_eq!(map.len(), 90);
+        assert_eq!(map.iter().count(), 90);
+        for i in 0..100 {
+            assert_eq!(map.get(&i).is_some(), !drained.contains(&i));
+        }
+        map.insert(drained[0], 0);
+        assert_eq!(map.len(), 91);
+    }
+
+    #[test]
+    fn drain_dropped_early() {
+        let drops = Cell::new(0);
+        let mut map = HashMap::with_seed(1);
+        for i in 0..100 {
+            map.insert(i, DropCounter(&drops));
+        }
+        map.remove(&0);
+        drops.set(0);
+
+        let mut drain = map.drain();
+        drain.next().unwrap();
+        drop(drain);
+        assert_eq!(drops.get(), 99);
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+        assert!(map.get(&50).is_none());
+    }
+
+    #[test]
+    fn full_table_lookups_terminate() {
+        let mut map = HashMap::with_seed(1);
+        let threshold = (INITIAL_CAPACITY as f64 * LOAD_FACTOR) as usize;
+        for i in 0..threshold as u32 {
+            map.insert(i, i);
+        }
+        // Turn half of the entries into tombstones, which probing goes past.
+        for i in 0..threshold as u32 / 2 {
+            map.remove(&i);
+        }
+        assert_eq!(map.capacity(), INITIAL_CAPACITY);
+        assert_eq!(map.len() + map.tombstones, threshold);
+        assert!(map.buckets.iter().any(|b| matches!(b, Bucket::Empty)));
+        for i in 100..200 {
+            assert!(map.get(&i).is_none());
+            assert!(map.remove(&i).is_none());
+        }
+
+        // The table is at the threshold, so one more key rehashes it instead
+        // of taking one of the last empty buckets.
+        map.insert(100, 100);
+        assert_eq!(map.tombstones, 0);
+        assert_eq!(map.get(&100), Some(&100));
+    }
+
+    #[test]
+    fn raw_entry_hashes_once() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static HASHES: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(PartialEq, Eq)]
+        struct CountedKey(u32);
+
+        impl Hash for CountedKey {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                HASHES.fetch_add(1, Ordering::Relaxed);
+                self.0.hash(state);
+            }
+        }
+
+        let mut map = HashMap::with_seed(1);
+        for i in 0..4 {
+            let key = CountedKey(i % 2);
+            let hash = map.hash_one(&key);
+            let (_, count) = map
+                .raw_entry_mut()
+                .from_key_hashed_nocheck(hash, &key)
+                .or_insert(key, 0);
+            *count += 1;
+        }
+        // One hash per get-or-insert.
+        assert_eq!(HASHES.load(Ordering::Relaxed), 4);
+        assert_eq!(map.len(), 2);
+
+        let hash = map.hash_one(&CountedKey(1));
+        let found = map.raw_entry().from_hash(hash, |k| k.0 == 1);
+        assert_eq!(found.map(|(_, v)| *v), Some(2));
+        let hash = map.hash_one(&CountedKey(2));
+        assert!(map.raw_entry().from_hash(hash, |k| k.0 == 2).is_none());
+    }
+
+    #[test]
+    fn overwrite_at_threshold_does_not_grow() {
+        let mut map = HashMap::with_seed(1);
+        let threshold = (INITIAL_CAPACITY as f64 * LOAD_FACTOR) as usize;
+        for i in 0..threshold as u32 {
+            map.insert(i, 0);
+        }
+        assert_eq!(map.insert(0, 1), Some(0));
+        let hash = map.hash_one(&1);
+        match map.raw_entry_mut().from_key_hashed_nocheck(hash, &1) {
+            RawEntryMut::Occupied(mut entry) => assert_eq!(entry.insert(1), 0),
+            RawEntryMut::Vacant(_) => panic!("key 1 is in the map"),
+        }
+        assert_eq!(map.capacity(), INITIAL_CAPACITY);
+
+        // A new key does grow the table.
+        map.insert(threshold as u32, 0);
+        assert_eq!(map.capacity(), INITIAL_CAPACITY * 2);
+    }
+
+    #[test]
+    fn seed_kept_across_resizes() {
+        let seed = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let mut map = HashMap::with_seed(seed);
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+        // 16 -> 32 -> 64 -> 128 -> 256 buckets.
+        assert_eq!(map.capacity(), 256);
+        assert_eq!(map.seed, seed);
+        assert_eq!(
+            map.hash_one(&7),
+            HashMap::<u32, u32>::with_seed(seed).hash_one(&7)
+        );
+        for i in 0..100 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! A hash set implemented as a [`HashMap`] with `()` values.
+
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::Hash;
+use core::iter::Chain;
+
+use super::hash_map::{self, HashMap};
+
+/// A hash set, storing its elements as the keys of a [`HashMap`].
+///
+/// Like the map, the iteration order is unspecified and differs between sets.
+pub struct HashSet<T> {
+    map: HashMap<T, ()>,
+}
+
+impl<T> HashSet<T> {
+    /// Creates an empty `HashSet`.
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+
+    /// Creates an empty `HashSet` with at least `capacity` buckets.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Creates an empty `HashSet` whose hasher is seeded by `seed`.
+    ///
+    /// See [`HashMap::with_seed`].
+    pub fn with_seed(seed: u128) -> Self {
+        Self {
+            map: HashMap::with_seed(seed),
+        }
+    }
+
+    /// Returns the number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the number of buckets of the set.
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// Removes all the elements, keeping the allocated buckets.
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// An iterator visiting all elements in arbitrary order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.map.keys(),
+        }
+    }
+}
+
+impl<T: Hash + Eq> HashSet<T> {
+    /// Adds a value to the set.
+    ///
+    /// Returns `true` if the set did not already contain it. Otherwise the set
+    /// is not modified, and the stored value is kept.
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.map.contains_key(&value) {
+            return false;
+        }
+        self.map.insert(value, ());
+        true
+    }
+
+    /// Returns `true` if the set contains the value.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Removes a value from the set. Returns `true` if it was present.
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    /// An iterator visiting the values in `self` or `other`, without
+    /// duplicates.
+    pub fn union<'a>(&'a self, other: &'a HashSet<T>) -> Union<'a, T> {
+        Union {
+            inner: self.iter().chain(other.difference(self)),
+        }
+    }
+
+    /// An iterator visiting the values in both `self` and `other`.
+    pub fn intersection<'a>(&'a self, other: &'a HashSet<T>) -> Intersection<'a, T> {
+        Intersection {
+            inner: self.iter(),
+            other,
+        }
+    }
+
+    /// An iterator visiting the values in `self` but not in `other`.
+    pub fn difference<'a>(&'a self, other: &'a HashSet<T>) -> Difference<'a, T> {
+        Difference {
+            inner: self.iter(),
+            other,
+        }
+    }
+}
+
+impl<T> Default for HashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for HashSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Hash + Eq> Extend<T> for HashSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Hash + Eq> FromIterator<T> for HashSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a, T> IntoIterator for &'a HashSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`HashSet`].
+pub struct Iter<'a, T> {
+    inner: hash_map::Keys<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// An iterator over the union of two [`HashSet`]s.
+///
+/// See [`HashSet::union`].
+pub struct Union<'a, T> {
+    inner: Chain<Iter<'a, T>, Difference<'a, T>>,
+}
+
+impl<'a, T: Hash + Eq> Iterator for Union<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+/// An iterator over the intersection of two [`HashSet`]s.
+///
+/// See [`HashSet::intersection`].
+pub struct Intersection<'a, T> {
+    inner: Iter<'a, T>,
+    other: &'a HashSet<T>,
+}
+
+impl<'a, T: Hash + Eq> Iterator for Intersection<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.inner.find(|value| other.contains(*value))
+    }
+}
+
+/// An iterator over the difference of two [`HashSet`]s.
+///
+/// See [`HashSet::difference`].
+pub struct Difference<'a, T> {
+    inner: Iter<'a, T>,
+    other: &'a HashSet<T>,
+}
+
+impl<'a, T: Hash + Eq> Iterator for Difference<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.inner.find(|value| !other.contains(*value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted<'a>(iter: impl Iterator<Item = &'a u32>) -> Vec<u32> {
+        let mut values: Vec<u32> = iter.copied().collect();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn insert_contains_remove() {
+        let mut set = HashSet::with_seed(1);
+        assert!(set.insert(1));
+        assert!(set.insert(2));
+        assert!(!set.insert(1));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&1));
+        assert!(set.remove(&1));
+        assert!(!set.remove(&1));
+        assert!(!set.contains(&1));
+        assert_eq!(sorted(set.iter()), [2]);
+    }
+
+    #[test]
+    fn insert_keeps_stored_value() {
+        let mut set = HashSet::with_seed(1);
+        let stored = String::from("a");
+        let stored_ptr = stored.as_ptr();
+        set.insert(stored);
+        assert!(!set.insert(String::from("a")));
+        assert_eq!(set.iter().next().unwrap().as_ptr(), stored_ptr);
+    }
+
+    #[test]
+    fn set_operations() {
+        let mut a = HashSet::with_seed(1);
+        a.extend([1, 2, 3, 4]);
+        let mut b = HashSet::with_seed(2);
+        b.extend([3, 4, 5]);
+
+        assert_eq!(sorted(a.union(&b)), [1, 2, 3, 4, 5]);
+        assert_eq!(sorted(a.intersection(&b)), [3, 4]);
+        assert_eq!(sorted(a.difference(&b)), [1, 2]);
+        assert_eq!(sorted(b.difference(&a)), [5]);
+    }
+}
@@ -0,0 +1,306 @@
+//! A fixed-capacity cache evicting the least recently used entry.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::Hash;
+use core::mem;
+
+use super::HashMap;
+
+/// No node: the end of the recency list.
+const NIL: usize = usize::MAX;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// The more recently used neighbour.
+    prev: usize,
+    /// The less recently used neighbour.
+    next: usize,
+}
+
+/// A cache holding at most `capacity` entries, which evicts the least recently
+/// used one to make room for a new one.
+///
+/// The entries are stored in a vector and linked in a list from the most to
+/// the least recently used, and a [`HashMap`] finds the entry of a key.
+/// Inserting or looking up an entry marks it as the most recently used.
+pub struct LruCache<K, V> {
+    map: HashMap<K, usize>,
+    nodes: Vec<Node<K, V>>,
+    /// The most recently used entry.
+    head: usize,
+    /// The least recently used entry, the next to be evicted.
+    tail: usize,
+    capacity: usize,
+}
+
+impl<K, V> LruCache<K, V> {
+    /// Creates an empty `LruCache` holding at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "LruCache capacity must not be zero");
+        Self {
+            map: HashMap::with_capacity(capacity * 2),
+            nodes: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            capacity,
+        }
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the cache contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the maximum number of entries of the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// An iterator visiting all key-value pairs, from the most to the least
+    /// recently used. It does not change the recency of the entries.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            nodes: &self.nodes,
+            next: self.head,
+        }
+    }
+
+    /// Unlinks the node `idx` from the recency list.
+    fn detach(&mut self, idx: usize) {
+        let (prev, next) = (self.nodes[idx].prev, self.nodes[idx].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.nodes[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.nodes[next].prev = prev,
+        }
+    }
+
+    /// Links the node `idx` at the front of the recency list.
+    fn push_front(&mut self, idx: usize) {
+        self.nodes[idx].prev = NIL;
+        self.nodes[idx].next = self.head;
+        match self.head {
+            NIL => self.tail = idx,
+            head => self.nodes[head].prev = idx,
+        }
+        self.head = idx;
+    }
+
+    /// Marks the node `idx` as the most recently used.
+    fn touch(&mut self, idx: usize) {
+        if self.head != idx {
+            self.detach(idx);
+            self.push_front(idx);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Returns a reference to the value corresponding to the key, and marks
+    /// the entry as the most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = *self.map.get(key)?;
+        self.touch(idx);
+        Some(&self.nodes[idx].value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, and
+    /// marks the entry as the most recently used.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = *self.map.get(key)?;
+        self.touch(idx);
+        Some(&mut self.nodes[idx].value)
+    }
+
+    /// Returns a reference to the value corresponding to the key, without
+    /// changing the recency of the entry.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&idx| &self.nodes[idx].value)
+    }
+
+    /// Returns `true` if the cache contains an entry for the specified key.
+    /// The recency of the entry does not change.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a key-value pair into the cache, as the most recently used
+    /// entry.
+    ///
+    /// If the cache already had this key present, the value is updated, and
+    /// the old value is returned. Otherwise, if the cache is full, the least
+    /// recently used entry is evicted first.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&idx) = self.map.get(&key) {
+            self.touch(idx);
+            return Some(mem::replace(&mut self.nodes[idx].value, value));
+        }
+        if self.nodes.len() == self.capacity {
+            self.remove_node(self.tail);
+        }
+        let idx = self.nodes.len();
+        self.map.insert(key.clone(), idx);
+        self.nodes.push(Node {
+            key,
+            value,
+            prev: NIL,
+            next: NIL,
+        });
+        self.push_front(idx);
+        None
+    }
+
+    /// Removes a key from the cache, returning the value at the key if the key
+    /// was previously in the cache.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = *self.map.get(key)?;
+        Some(self.remove_node(idx).1)
+    }
+
+    /// Removes and returns the least recently used entry.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        match self.tail {
+            NIL => None,
+            tail => Some(self.remove_node(tail)),
+        }
+    }
+
+    /// Removes the node `idx`, moving the last node of the vector into its
+    /// slot.
+    fn remove_node(&mut self, idx: usize) -> (K, V) {
+        self.detach(idx);
+        let last = self.nodes.len() - 1;
+        if idx != last {
+            self.nodes.swap(idx, last);
+            // The moved node keeps its place in the list, at its new index.
+            let (prev, next) = (self.nodes[idx].prev, self.nodes[idx].next);
+            match prev {
+                NIL => self.head = idx,
+                prev => self.nodes[prev].next = idx,
+            }
+            match next {
+                NIL => self.tail = idx,
+                next => self.nodes[next].prev = idx,
+            }
+            *self.map.get_mut(&self.nodes[idx].key).unwrap() = idx;
+        }
+        let node = self.nodes.pop().unwrap();
+        self.map.remove(&node.key);
+        (node.key, node.value)
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the entries of a [`LruCache`], from the most to the least
+/// recently used.
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    next: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.get(self.next)?;
+        self.next = node.next;
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(cache: &LruCache<u32, u32>) -> Vec<u32> {
+        cache.iter().map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn eviction_order() {
+        let mut cache = LruCache::new(3);
+        for i in 1..=3 {
+            cache.insert(i, i * 10);
+        }
+        assert_eq!(keys(&cache), [3, 2, 1]);
+
+        // Looking 1 up makes 2 the least recently used entry.
+        assert_eq!(cache.get(&1), Some(&10));
+        assert_eq!(cache.insert(4, 40), None);
+        assert_eq!(keys(&cache), [4, 1, 3]);
+        assert!(!cache.contains_key(&2));
+
+        // Peeking does not change the order, updating does.
+        assert_eq!(cache.peek(&3), Some(&30));
+        assert_eq!(cache.insert(3, 31), Some(30));
+        cache.insert(5, 50);
+        assert_eq!(keys(&cache), [5, 3, 4]);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn remove_and_pop_lru() {
+        let mut cache = LruCache::new(4);
+        for i in 1..=4 {
+            cache.insert(i, i);
+        }
+        // Removing from the middle moves the last node into its slot.
+        assert_eq!(cache.remove(&2), Some(2));
+        assert_eq!(keys(&cache), [4, 3, 1]);
+        assert_eq!(cache.pop_lru(), Some((1, 1)));
+        cache.insert(5, 5);
+        assert_eq!(keys(&cache), [5, 4, 3]);
+        for i in [3, 4, 5] {
+            assert_eq!(cache.peek(&i), Some(&i));
+        }
+        while cache.pop_lru().is_some() {}
+        assert!(cache.is_empty());
+        assert_eq!(cache.pop_lru(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be zero")]
+    fn zero_capacity() {
+        LruCache::<u32, u32>::new(0);
+    }
+}
@@ -0,0 +1,19 @@
+//! Collection types.
+//!
+//! Re-exports the collections of [`alloc::collections`], plus a [`HashMap`]
+//! whose hasher is seeded by the platform random number generator and a
+//! [`HashSet`] over it, an [`OrderedMap`] with range queries and a
+//! [`LruCache`].
+
+#[doc(no_inline)]
+pub use alloc::collections::*;
+
+pub mod hash_map;
+pub mod hash_set;
+pub mod lru_cache;
+mod ordered_map;
+
+pub use self::hash_map::HashMap;
+pub use self::hash_set::HashSet;
+pub use self::lru_cache::LruCache;
+pub use self::ordered_map::OrderedMap;
@@ -0,0 +1,191 @@
+//! An ordered map over a [`BTreeMap`], with range queries.
+
+use alloc::collections::btree_map::{self, BTreeMap};
+use core::borrow::Borrow;
+use core::fmt;
+use core::ops::RangeBounds;
+
+/// A map with its keys kept in order, backed by a [`BTreeMap`].
+///
+/// It gives exercise code a small and stable API for ordered lookups, such as
+/// the keys in a range or the smallest and largest keys.
+pub struct OrderedMap<K, V> {
+    inner: BTreeMap<K, V>,
+}
+
+impl<K, V> OrderedMap<K, V> {
+    /// Creates an empty `OrderedMap`.
+    pub const fn new() -> Self {
+        Self {
+            inner: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Removes all the elements.
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    /// An iterator visiting all key-value pairs, in increasing order of keys.
+    pub fn iter(&self) -> btree_map::Iter<'_, K, V> {
+        self.inner.iter()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> {
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map already had this key present, the value is updated, and the
+    /// old value is returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.inner.insert(key, value)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.inner.get(key)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.inner.contains_key(key)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.inner.remove(key)
+    }
+
+    /// An iterator over the key-value pairs with keys in `range`, in
+    /// increasing order of keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if they
+    /// are equal and both excluded.
+    pub fn range<Q, R>(&self, range: R) -> btree_map::Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.inner.range(range)
+    }
+
+    /// Returns the key-value pair with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.inner.first_key_value()
+    }
+
+    /// Returns the key-value pair with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.inner.last_key_value()
+    }
+}
+
+impl<K, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OrderedMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for OrderedMap<K, V> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        self.inner.extend(iter)
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self {
+            inner: BTreeMap::from_iter(iter),
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a OrderedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = btree_map::Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_iteration() {
+        let mut map: OrderedMap<_, _> = [(3, "c"), (1, "a"), (2, "b")].into_iter().collect();
+        assert!(map.iter().map(|(k, _)| *k).eq([1, 2, 3]));
+        assert_eq!(map.first_key_value(), Some((&1, &"a")));
+        assert_eq!(map.last_key_value(), Some((&3, &"c")));
+
+        assert_eq!(map.insert(2, "B"), Some("b"));
+        assert_eq!(map.remove(&1), Some("a"));
+        assert!(!map.contains_key(&1));
+        assert!(map.iter().eq([(&2, &"B"), (&3, &"c")]));
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.first_key_value(), None);
+    }
+
+    #[test]
+    fn range() {
+        let map: OrderedMap<u32, u32> = (0..10).map(|i| (i * 10, i)).collect();
+        let keys = |r: btree_map::Range<'_, u32, u32>| r.map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(keys(map.range(20..50)), [20, 30, 40]);
+        assert_eq!(keys(map.range(15..=50)), [20, 30, 40, 50]);
+        assert_eq!(keys(map.range(..20)), [0, 10]);
+        assert_eq!(keys(map.range(85..)), [90]);
+        assert_eq!(keys(map.range(41..49)), []);
+    }
+
+    #[test]
+    #[should_panic]
+    fn range_start_after_end() {
+        let map: OrderedMap<u32, u32> = (0..10).map(|i| (i, i)).collect();
+        #[allow(clippy::reversed_empty_ranges)]
+        let _ = map.range(5..1);
+    }
+
+    #[test]
+    fn borrowed_lookup() {
+        let mut map = OrderedMap::new();
+        map.insert(String::from("b"), 2);
+        map.insert(String::from("a"), 1);
+        assert_eq!(map.get("a"), Some(&1));
+    }
+}
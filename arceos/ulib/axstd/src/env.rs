@@ -4,7 +4,144 @@
 extern crate alloc;
 
 #[cfg(feature = "fs")]
-use {crate::io, alloc::string::String};
+use crate::io;
+#[cfg(any(feature = "alloc", feature = "fs"))]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use {
+    crate::sync::Mutex,
+    alloc::{collections::btree_map::BTreeMap, string::ToString, vec::IntoIter},
+    core::{fmt, iter::Chain, option, str::SplitAsciiWhitespace},
+};
+
+/// Environment variables of the app, set by [`set_var`].
+#[cfg(feature = "alloc")]
+static VARS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// An iterator over the arguments of a process, yielding a [`String`] value
+/// for each argument.
+///
+/// This structure is created by [`args`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct Args {
+    inner: Chain<option::IntoIter<&'static str>, SplitAsciiWhitespace<'static>>,
+}
+
+/// Returns the arguments that this program was started with.
+///
+/// ArceOS apps are linked with the kernel, so the arguments are fixed at build
+/// time: the first element is the app name, followed by the words of the
+/// `ARGS` make variable (e.g. `make A=path/to/app ARGS="foo bar" run`).
+#[cfg(feature = "alloc")]
+pub fn args() -> Args {
+    let name = option_env!("AX_APP_NAME").filter(|name| !name.is_empty());
+    let args = option_env!("AX_ARGS").unwrap_or("");
+    Args {
+        inner: name.into_iter().chain(args.split_ascii_whitespace()),
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Iterator for Args {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.inner.next().map(ToString::to_string)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DoubleEndedIterator for Args {
+    fn next_back(&mut self) -> Option<String> {
+        self.inner.next_back().map(ToString::to_string)
+    }
+}
+
+/// The error type for operations interacting with environment variables.
+///
+/// Possibly returned from [`var`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum VarError {
+    /// The specified environment variable was not present in the current
+    /// process's environment.
+    NotPresent,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for VarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            VarError::NotPresent => write!(f, "environment variable not found"),
+        }
+    }
+}
+
+/// An iterator over a snapshot of the environment variables of this process.
+///
+/// This structure is created by [`vars`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct Vars {
+    inner: IntoIter<(String, String)>,
+}
+
+#[cfg(feature = "alloc")]
+impl Iterator for Vars {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<(String, String)> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Returns an iterator of (variable, value) pairs, for all the environment
+/// variables of the current process, sorted by name.
+///
+/// The returned iterator contains a snapshot of the environment at the time of
+/// this invocation. Modifications made afterwards will not be reflected.
+#[cfg(feature = "alloc")]
+pub fn vars() -> Vars {
+    let vars = VARS
+        .lock()
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect::<alloc::vec::Vec<_>>();
+    Vars {
+        inner: vars.into_iter(),
+    }
+}
+
+/// Fetches the environment variable `key` from the current process.
+///
+/// Returns [`VarError::NotPresent`] if the variable is not set.
+#[cfg(feature = "alloc")]
+pub fn var(key: &str) -> Result<String, VarError> {
+    VARS.lock().get(key).cloned().ok_or(VarError::NotPresent)
+}
+
+/// Sets the environment variable `key` to the value `value` for the currently
+/// running process, overwriting any previous value.
+#[cfg(feature = "alloc")]
+pub fn set_var(key: &str, value: &str) {
+    VARS.lock().insert(key.into(), value.into());
+}
+
+/// Removes an environment variable from the environment of the currently
+/// running process.
+#[cfg(feature = "alloc")]
+pub fn remove_var(key: &str) {
+    VARS.lock().remove(key);
+}
 
 /// Returns the current working directory as a [`String`].
 #[cfg(feature = "fs")]
@@ -17,3 +154,40 @@ pub fn current_dir() -> io::Result<String> {
 pub fn set_current_dir(path: &str) -> io::Result<()> {
     arceos_api::fs::ax_set_current_dir(path)
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_get_remove_vars() {
+        assert_eq!(var("AX_TEST_B"), Err(VarError::NotPresent));
+        set_var("AX_TEST_B", "1");
+        set_var("AX_TEST_A", "2");
+        assert_eq!(var("AX_TEST_B").as_deref(), Ok("1"));
+
+        set_var("AX_TEST_B", "3");
+        assert_eq!(var("AX_TEST_B").as_deref(), Ok("3"));
+
+        // A snapshot, sorted by name.
+        let snapshot = vars();
+        set_var("AX_TEST_C", "4");
+        let pairs: Vec<_> = snapshot
+            .filter(|(k, _)| k.starts_with("AX_TEST_"))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("AX_TEST_A".into(), "2".into()),
+                ("AX_TEST_B".into(), "3".into())
+            ]
+        );
+
+        remove_var("AX_TEST_B");
+        assert_eq!(var("AX_TEST_B"), Err(VarError::NotPresent));
+        assert_eq!(
+            VarError::NotPresent.to_string(),
+            "environment variable not found"
+        );
+    }
+}
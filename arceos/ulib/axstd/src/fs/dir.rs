@@ -145,10 +145,25 @@ impl DirBuilder {
         }
     }
 
-    fn create_dir_all(&self, _path: &str) -> Result<()> {
-        axerrno::ax_err!(
-            Unsupported,
-            "Recursive directory creation is not supported yet"
-        )
+    fn create_dir_all(&self, path: &str) -> Result<()> {
+        if path.is_empty() {
+            return Ok(());
+        }
+        match api::ax_create_dir(path) {
+            Ok(()) => return Ok(()),
+            Err(axerrno::AxError::NotFound) => {}
+            Err(_) if super::metadata(path).map_or(false, |m| m.is_dir()) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        // the parent is missing, create it first
+        match path.trim_end_matches('/').rsplit_once('/') {
+            Some((parent, _)) if !parent.is_empty() => self.create_dir_all(parent)?,
+            _ => return axerrno::ax_err!(NotFound),
+        }
+        match api::ax_create_dir(path) {
+            Ok(()) => Ok(()),
+            Err(_) if super::metadata(path).map_or(false, |m| m.is_dir()) => Ok(()),
+            Err(e) => Err(e),
+        }
     }
 }
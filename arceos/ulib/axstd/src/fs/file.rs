@@ -158,6 +158,17 @@ impl File {
         api::ax_truncate_file(&self.inner, size)
     }
 
+    /// Attempts to sync all OS-internal metadata to disk.
+    pub fn sync_all(&self) -> Result<()> {
+        api::ax_sync_file(&self.inner)
+    }
+
+    /// Like [`File::sync_all`], except that it might not synchronize file
+    /// metadata to the filesystem.
+    pub fn sync_data(&self) -> Result<()> {
+        api::ax_sync_file(&self.inner)
+    }
+
     /// Queries metadata about the underlying file.
     pub fn metadata(&self) -> Result<Metadata> {
         api::ax_file_attr(&self.inner).map(Metadata)
@@ -6,7 +6,7 @@ mod file;
 use crate::io::{self, prelude::*};
 
 #[cfg(feature = "alloc")]
-use alloc::{string::String, vec::Vec};
+use alloc::{format, string::String, vec::Vec};
 
 pub use self::dir::{DirBuilder, DirEntry, ReadDir};
 pub use self::file::{File, FileType, Metadata, OpenOptions, Permissions};
@@ -63,6 +63,25 @@ pub fn remove_dir(path: &str) -> io::Result<()> {
     arceos_api::fs::ax_remove_dir(path)
 }
 
+/// Removes a directory at this path, after removing all its contents.
+///
+/// Sub-directories are removed recursively before their parent.
+#[cfg(feature = "alloc")]
+pub fn remove_dir_all(path: &str) -> io::Result<()> {
+    // collect first, removing entries while iterating may skip some of them
+    let entries = read_dir(path)?
+        .map(|entry| entry.map(|e| (e.path(), e.file_type())))
+        .collect::<io::Result<Vec<_>>>()?;
+    for (child, ty) in entries {
+        if ty.is_dir() {
+            remove_dir_all(&child)?;
+        } else {
+            remove_file(&child)?;
+        }
+    }
+    remove_dir(path)
+}
+
 /// Removes a file from the filesystem.
 pub fn remove_file(path: &str) -> io::Result<()> {
     arceos_api::fs::ax_remove_file(path)
@@ -75,3 +94,66 @@ pub fn remove_file(path: &str) -> io::Result<()> {
 pub fn rename(old: &str, new: &str) -> io::Result<()> {
     arceos_api::fs::ax_rename(old, new)
 }
+
+/// Copies the contents of one file to another, returns the total number of
+/// bytes copied.
+///
+/// The destination file is created if it does not exist, and will be
+/// truncated if it does.
+pub fn copy(from: &str, to: &str) -> io::Result<u64> {
+    let mut reader = File::open(from)?;
+    let mut writer = File::create(to)?;
+    let mut buf = [0; 512];
+    let mut copied = 0;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(copied);
+        }
+        writer.write_all(&buf[..n])?;
+        copied += n as u64;
+    }
+}
+
+/// Returns the canonical, absolute form of a path, with all `.` and `..`
+/// components resolved, relative paths being resolved against the current
+/// directory.
+///
+/// Returns an error if the path does not exist, or if a `..` component goes
+/// above the root directory.
+#[cfg(feature = "alloc")]
+pub fn canonicalize(path: &str) -> io::Result<String> {
+    arceos_api::fs::ax_canonicalize(path)
+}
+
+/// The directory in which [`temp_file`] creates its files.
+///
+/// It is the mount point of a ramfs when axfs is built with the `ramfs`
+/// feature (the default), and is created by [`temp_file`] if missing
+/// otherwise.
+pub const TEMP_DIR: &str = "/tmp";
+
+/// Creates a new, empty file with a unique name in [`TEMP_DIR`], opened for
+/// reading and writing, and returns its path along with it.
+///
+/// The name is `prefix` followed by a random number from the platform random
+/// number generator, drawn again if a file of that name already exists. The
+/// file is not removed automatically.
+#[cfg(feature = "alloc")]
+pub fn temp_file(prefix: &str) -> io::Result<(String, File)> {
+    create_dir_all(TEMP_DIR)?;
+    loop {
+        let suffix = arceos_api::sys::ax_random() as u64;
+        let path = format!("{}/{}{:016x}", TEMP_DIR, prefix, suffix);
+        let opened = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path);
+        match opened {
+            Ok(file) => return Ok((path, file)),
+            Err(io::Error::AlreadyExists) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
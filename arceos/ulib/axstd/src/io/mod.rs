@@ -1,13 +1,24 @@
 //! Traits, helpers, and type definitions for core I/O functionality.
 
+mod read_ext;
 mod stdio;
 
-pub use axio::prelude;
 pub use axio::{BufRead, BufReader, Error, Read, Seek, SeekFrom, Write};
 
+pub use self::read_ext::{Bytes, Chain, ReadExt, Take};
+
 #[doc(hidden)]
-pub use self::stdio::__print_impl;
-pub use self::stdio::{stdin, stdout, Stdin, StdinLock, Stdout, StdoutLock};
+pub use self::stdio::{__eprint_impl, __print_impl};
+pub use self::stdio::{
+    stderr, stdin, stdout, Stderr, StderrLock, Stdin, StdinLock, Stdout, StdoutLock,
+};
+
+/// The I/O prelude, to glob-import the I/O traits with
+/// `use axstd::io::prelude::*`.
+pub mod prelude {
+    pub use super::ReadExt;
+    pub use axio::prelude::*;
+}
 
 /// A specialized [`Result`] type for I/O operations.
 ///
@@ -26,3 +37,111 @@ pub use self::stdio::{stdin, stdout, Stdin, StdinLock, Stdout, StdoutLock};
 /// [`axstd::io`]: crate::io
 /// [`io::Error`]: Error
 pub type Result<T> = axio::Result<T>;
+
+/// Copies the entire contents of a reader into a writer.
+///
+/// This function will continuously read data from `reader` and then write it
+/// into `writer` in a streaming fashion until `reader` returns EOF.
+///
+/// On success, the total number of bytes that were copied from `reader` to
+/// `writer` is returned. The first error of a read or a write is returned as
+/// is, and the data copied before it is not reported.
+pub fn copy<R, W>(reader: &mut R, writer: &mut W) -> Result<u64>
+where
+    R: Read + ?Sized,
+    W: Write + ?Sized,
+{
+    let mut buf = [0; 1024];
+    let mut copied = 0;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(copied);
+        }
+        writer.write_all(&buf[..n])?;
+        copied += n as u64;
+    }
+}
+
+/// Runs `f` until it returns anything other than an [`Error::WouldBlock`]
+/// error, yielding the CPU between the attempts.
+///
+/// This is meant for nonblocking operations (e.g. on nonblocking sockets)
+/// that should be waited for. Other errors are returned as is.
+pub fn retry_would_block<T, F>(mut f: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    loop {
+        match f() {
+            Err(Error::WouldBlock) => crate::thread::yield_now(),
+            res => return res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A writer which takes at most 3 bytes per write.
+    struct ShortWriter(Vec<u8>);
+
+    impl Write for ShortWriter {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            let n = buf.len().min(3);
+            self.0.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn copy_all() {
+        let data: Vec<u8> = (0..3000).map(|i| i as u8).collect();
+        let mut reader = &data[..];
+        let mut writer = Vec::new();
+        assert_eq!(copy(&mut reader, &mut writer).unwrap(), 3000);
+        assert_eq!(writer, data);
+        assert!(reader.is_empty());
+
+        let mut empty: &[u8] = &[];
+        assert_eq!(copy(&mut empty, &mut writer).unwrap(), 0);
+    }
+
+    #[test]
+    fn copy_short_writes() {
+        let mut reader: &[u8] = b"hello, world";
+        let mut writer = ShortWriter(Vec::new());
+        assert_eq!(copy(&mut reader, &mut writer).unwrap(), 12);
+        assert_eq!(writer.0, b"hello, world");
+    }
+
+    #[test]
+    fn retry_would_block_until_ready() {
+        let mut attempts = 0;
+        let res = retry_would_block(|| {
+            attempts += 1;
+            if attempts <= 2 {
+                Err(Error::WouldBlock)
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(res, Ok(3));
+    }
+
+    #[test]
+    fn retry_would_block_other_errors() {
+        let mut attempts = 0;
+        let res: Result<()> = retry_would_block(|| {
+            attempts += 1;
+            Err(Error::InvalidData)
+        });
+        assert_eq!(res, Err(Error::InvalidData));
+        assert_eq!(attempts, 1);
+    }
+}
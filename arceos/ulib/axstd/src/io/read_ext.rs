@@ -0,0 +1,240 @@
+//! Adapters over [`Read`], which `axio` does not provide.
+
+use super::{Read, Result};
+
+/// Extension methods for every [`Read`] type.
+///
+/// It is in the [I/O prelude](super::prelude), along with [`Read`] itself.
+pub trait ReadExt: Read {
+    /// Transforms this reader into an iterator over its bytes.
+    ///
+    /// Each byte is read with its own call to [`Read::read`], so wrap an
+    /// unbuffered reader in a [`BufReader`](super::BufReader) first.
+    ///
+    /// The iterator yields `Err` for the errors of the reader, and ends at
+    /// EOF.
+    fn bytes(self) -> Bytes<Self>
+    where
+        Self: Sized,
+    {
+        Bytes { inner: self }
+    }
+
+    /// Creates an adapter which reads at most `limit` bytes from this reader,
+    /// then reports EOF.
+    ///
+    /// It never reads past the limit from this reader, so the bytes after it
+    /// are left for [`Take::into_inner`].
+    fn take(self, limit: u64) -> Take<Self>
+    where
+        Self: Sized,
+    {
+        Take { inner: self, limit }
+    }
+
+    /// Creates an adapter which reads all the bytes of this reader, then all
+    /// the bytes of `next`.
+    ///
+    /// A single [`Read::read`] call returns bytes of only one of them, so use
+    /// [`Read::read_exact`] to read across the boundary.
+    fn chain<R: Read>(self, next: R) -> Chain<Self, R>
+    where
+        Self: Sized,
+    {
+        Chain {
+            first: self,
+            second: next,
+            done_first: false,
+        }
+    }
+}
+
+impl<R: Read + ?Sized> ReadExt for R {}
+
+/// An iterator over the bytes of a reader.
+///
+/// Created by [`ReadExt::bytes`].
+#[derive(Debug)]
+pub struct Bytes<R> {
+    inner: R,
+}
+
+impl<R: Read> Iterator for Bytes<R> {
+    type Item = Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut byte = 0;
+        match self.inner.read(core::slice::from_mut(&mut byte)) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(byte)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// A reader which reads at most a given number of bytes from another one.
+///
+/// Created by [`ReadExt::take`].
+#[derive(Debug)]
+pub struct Take<R> {
+    inner: R,
+    limit: u64,
+}
+
+impl<R> Take<R> {
+    /// Returns the number of bytes that can still be read before EOF.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Consumes the `Take`, returning the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Gets a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the wrapped reader.
+    ///
+    /// Reading from it directly does not count against the limit.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+}
+
+impl<R: Read> Read for Take<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if self.limit == 0 {
+            return Ok(0);
+        }
+        let max = buf.len().min(self.limit.try_into().unwrap_or(usize::MAX));
+        let n = self.inner.read(&mut buf[..max])?;
+        self.limit -= n as u64;
+        Ok(n)
+    }
+}
+
+/// A reader which reads all the bytes of one reader, then of another one.
+///
+/// Created by [`ReadExt::chain`].
+#[derive(Debug)]
+pub struct Chain<T, U> {
+    first: T,
+    second: U,
+    done_first: bool,
+}
+
+impl<T, U> Chain<T, U> {
+    /// Consumes the `Chain`, returning the wrapped readers.
+    pub fn into_inner(self) -> (T, U) {
+        (self.first, self.second)
+    }
+
+    /// Gets references to the wrapped readers.
+    pub fn get_ref(&self) -> (&T, &U) {
+        (&self.first, &self.second)
+    }
+
+    /// Gets mutable references to the wrapped readers.
+    pub fn get_mut(&mut self) -> (&mut T, &mut U) {
+        (&mut self.first, &mut self.second)
+    }
+}
+
+impl<T: Read, U: Read> Read for Chain<T, U> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if !self.done_first {
+            match self.first.read(buf)? {
+                // An empty `buf` does not mean that `first` is at EOF.
+                0 if !buf.is_empty() => self.done_first = true,
+                n => return Ok(n),
+            }
+        }
+        self.second.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Error;
+
+    /// A reader which fails every read.
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> Result<usize> {
+            Err(Error::InvalidData)
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        let reader: &[u8] = b"abc";
+        let mut bytes = reader.bytes();
+        assert_eq!(bytes.next(), Some(Ok(b'a')));
+        assert_eq!(bytes.next(), Some(Ok(b'b')));
+        assert_eq!(bytes.next(), Some(Ok(b'c')));
+        assert_eq!(bytes.next(), None);
+        assert_eq!(bytes.next(), None);
+
+        let mut bytes = FailingReader.bytes();
+        assert_eq!(bytes.next(), Some(Err(Error::InvalidData)));
+    }
+
+    #[test]
+    fn take() {
+        let mut inner: &[u8] = b"0123456789";
+        let mut take = (&mut inner).take(5);
+        let mut buf = [0; 8];
+        assert_eq!(take.read(&mut buf).unwrap(), 5);
+        assert_eq!(&buf[..5], b"01234");
+        assert_eq!(take.limit(), 0);
+        assert_eq!(take.read(&mut buf).unwrap(), 0);
+
+        // Nothing past the limit was consumed from the inner reader.
+        assert_eq!(*take.get_ref(), b"56789");
+    }
+
+    #[test]
+    fn take_into_inner() {
+        let reader: &[u8] = b"0123456789";
+        let mut take = reader.take(4);
+        let mut buf = [0; 3];
+        assert_eq!(take.read(&mut buf).unwrap(), 3);
+        assert_eq!(take.limit(), 1);
+        assert_eq!(take.read(&mut buf).unwrap(), 1);
+        assert_eq!(take.into_inner(), b"456789");
+    }
+
+    #[test]
+    fn chain() {
+        let first: &[u8] = b"head:";
+        let second: &[u8] = b"body";
+        let mut chain = first.chain(second);
+
+        // A single read stops at the boundary, read_exact goes across it.
+        let mut buf = [0; 9];
+        assert_eq!(chain.read(&mut buf).unwrap(), 5);
+        let mut chain = first.chain(second);
+        chain.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"head:body");
+        assert_eq!(chain.read(&mut buf).unwrap(), 0);
+
+        // An empty buffer does not skip the first reader.
+        let mut chain = first.chain(second);
+        assert_eq!(chain.read(&mut []).unwrap(), 0);
+        assert_eq!(chain.read(&mut buf).unwrap(), 5);
+        assert_eq!(&buf[..5], b"head:");
+    }
+
+    #[test]
+    fn chain_empty_readers() {
+        let empty: &[u8] = b"";
+        let bytes: Result<Vec<u8>> = empty.chain(&b"ab"[..]).chain(empty).bytes().collect();
+        assert_eq!(bytes.unwrap(), b"ab");
+    }
+}
@@ -4,6 +4,9 @@ use crate::sync::{Mutex, MutexGuard};
 #[cfg(feature = "alloc")]
 use alloc::{string::String, vec::Vec};
 
+#[cfg(not(test))]
+use arceos_api::stdio as console;
+
 struct StdinRaw;
 struct StdoutRaw;
 
@@ -12,7 +15,7 @@ impl Read for StdinRaw {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut read_len = 0;
         while read_len < buf.len() {
-            if let Some(c) = arceos_api::stdio::ax_console_read_byte() {
+            if let Some(c) = console::ax_console_read_byte() {
                 buf[read_len] = c;
                 read_len += 1;
             } else {
@@ -25,7 +28,7 @@ impl Read for StdinRaw {
 
 impl Write for StdoutRaw {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        arceos_api::stdio::ax_console_write_bytes(buf)
+        console::ax_console_write_bytes(buf)
     }
     fn flush(&mut self) -> io::Result<()> {
         Ok(())
@@ -35,6 +38,7 @@ impl Write for StdoutRaw {
 /// A handle to the standard input stream of a process.
 pub struct Stdin {
     inner: &'static Mutex<BufReader<StdinRaw>>,
+    echo: bool,
 }
 
 /// A locked reference to the [`Stdin`] handle.
@@ -58,9 +62,96 @@ impl Stdin {
     }
 
     /// Locks this handle and reads a line of input, appending it to the specified buffer.
+    ///
+    /// Blocks until `\r` or `\n` is read, and appends the line ending with
+    /// `\n`. Backspace and delete erase the last character of the line. The
+    /// input is echoed to the console unless disabled by [`Stdin::set_echo`].
+    ///
+    /// Returns the number of bytes appended.
     #[cfg(feature = "alloc")]
     pub fn read_line(&self, buf: &mut String) -> io::Result<usize> {
-        self.inner.lock().read_line(buf)
+        let mut inner = self.inner.lock();
+        let mut line = Vec::new();
+        loop {
+            let mut c = 0;
+            if inner.read(core::slice::from_mut(&mut c))? == 0 {
+                crate::thread::yield_now();
+                continue;
+            }
+            let edit = edit_line(&mut line, c);
+            if self.echo {
+                StdoutRaw.write_all(edit.echo())?;
+            }
+            if let LineEdit::End = edit {
+                break;
+            }
+        }
+        let line = core::str::from_utf8(&line).map_err(|_| io::Error::InvalidData)?;
+        buf.push_str(line);
+        Ok(line.len())
+    }
+
+    /// Sets whether [`Stdin::read_line`] on this handle echoes its input to
+    /// the console.
+    ///
+    /// Echo is enabled on every handle returned by [`stdin`], and turning it
+    /// off only affects this one.
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+}
+
+/// What [`edit_line`] did with an input byte.
+#[cfg(feature = "alloc")]
+enum LineEdit {
+    /// The line ended, and `\n` was appended.
+    End,
+    /// The byte was appended.
+    Push(u8),
+    /// The last character was erased.
+    Erase,
+    /// There was no character to erase.
+    Nothing,
+}
+
+#[cfg(feature = "alloc")]
+impl LineEdit {
+    /// The bytes to echo to the console for this edit.
+    fn echo(&self) -> &[u8] {
+        match self {
+            Self::End => b"\n",
+            Self::Push(c) => core::slice::from_ref(c),
+            Self::Erase => b"\x08 \x08",
+            Self::Nothing => b"",
+        }
+    }
+}
+
+/// Applies the input byte `c` to the `line` being read by
+/// [`Stdin::read_line`].
+#[cfg(feature = "alloc")]
+fn edit_line(line: &mut Vec<u8>, c: u8) -> LineEdit {
+    match c {
+        b'\r' | b'\n' => {
+            line.push(b'\n');
+            LineEdit::End
+        }
+        // backspace or delete
+        0x08 | 0x7f => {
+            // drop a whole UTF-8 character
+            let mut erased = LineEdit::Nothing;
+            while let Some(b) = line.pop() {
+                erased = LineEdit::Erase;
+                if b & 0xc0 != 0x80 {
+                    break;
+                }
+            }
+            erased
+        }
+        c => {
+            line.push(c);
+            LineEdit::Push(c)
+        }
     }
 }
 
@@ -149,10 +240,56 @@ impl Write for StdoutLock<'_> {
     }
 }
 
+/// A handle to the standard error stream of the current process.
+///
+/// It writes to the same console as [`Stdout`], but has its own lock.
+pub struct Stderr {
+    inner: &'static Mutex<StdoutRaw>,
+}
+
+/// A locked reference to the [`Stderr`] handle.
+pub struct StderrLock<'a> {
+    inner: MutexGuard<'a, StdoutRaw>,
+}
+
+impl Stderr {
+    /// Locks this handle to the standard error stream, returning a writable
+    /// guard.
+    ///
+    /// The lock is released when the returned lock goes out of scope. The
+    /// returned guard also implements the `Write` trait for writing data.
+    pub fn lock(&self) -> StderrLock<'static> {
+        StderrLock {
+            inner: self.inner.lock(),
+        }
+    }
+}
+
+impl Write for Stderr {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.lock().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.lock().flush()
+    }
+}
+
+impl Write for StderrLock<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 /// Constructs a new handle to the standard input of the current process.
 pub fn stdin() -> Stdin {
     static INSTANCE: Mutex<BufReader<StdinRaw>> = Mutex::new(BufReader::new(StdinRaw));
-    Stdin { inner: &INSTANCE }
+    Stdin {
+        inner: &INSTANCE,
+        echo: true,
+    }
 }
 
 /// Constructs a new handle to the standard output of the current process.
@@ -161,13 +298,160 @@ pub fn stdout() -> Stdout {
     Stdout { inner: &INSTANCE }
 }
 
+/// Constructs a new handle to the standard error of the current process.
+pub fn stderr() -> Stderr {
+    static INSTANCE: Mutex<StdoutRaw> = Mutex::new(StdoutRaw);
+    Stderr { inner: &INSTANCE }
+}
+
 #[doc(hidden)]
 pub fn __print_impl(args: core::fmt::Arguments) {
+    // Hold the lock of `Stdout` in any case, so the output is ordered with
+    // that of its `Write` implementation.
+    let mut lock = stdout().lock();
     if cfg!(feature = "smp") {
         // synchronize using the lock in axlog, to avoid interleaving
         // with kernel logs
-        arceos_api::stdio::ax_console_write_fmt(args).unwrap();
+        console::ax_console_write_fmt(args).unwrap();
+    } else {
+        lock.write_fmt(args).unwrap();
+    }
+}
+
+#[doc(hidden)]
+pub fn __eprint_impl(args: core::fmt::Arguments) {
+    let mut lock = stderr().lock();
+    if cfg!(feature = "smp") {
+        console::ax_console_write_fmt(args).unwrap();
     } else {
-        stdout().lock().write_fmt(args).unwrap();
+        lock.write_fmt(args).unwrap();
+    }
+}
+
+/// An in-memory console for unit tests, with input and output per thread.
+#[cfg(test)]
+mod console {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    std::thread_local! {
+        /// The bytes the console has yet to read.
+        static INPUT: RefCell<VecDeque<u8>> = const { RefCell::new(VecDeque::new()) };
+        /// The bytes written to the console.
+        static OUTPUT: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    }
+
+    pub fn ax_console_read_byte() -> Option<u8> {
+        INPUT.with(|input| input.borrow_mut().pop_front())
+    }
+
+    pub fn ax_console_write_bytes(buf: &[u8]) -> crate::io::Result<usize> {
+        OUTPUT.with(|output| output.borrow_mut().extend_from_slice(buf));
+        Ok(buf.len())
+    }
+
+    pub fn ax_console_write_fmt(args: core::fmt::Arguments) -> core::fmt::Result {
+        ax_console_write_bytes(std::fmt::format(args).as_bytes()).map_err(|_| core::fmt::Error)?;
+        Ok(())
+    }
+
+    /// Queues `input` for the console to read.
+    pub fn feed(input: &[u8]) {
+        INPUT.with(|i| i.borrow_mut().extend(input));
+    }
+
+    /// Takes all the bytes written to the console so far.
+    pub fn take_output() -> Vec<u8> {
+        OUTPUT.with(|output| output.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn line_editing() {
+        let mut line = Vec::new();
+        for &c in b"ab\x08c\xc3\xa9\x7fd" {
+            assert!(!matches!(edit_line(&mut line, c), LineEdit::End));
+        }
+        assert_eq!(line, b"acd");
+        assert!(matches!(edit_line(&mut line, b'\r'), LineEdit::End));
+        assert_eq!(line, b"acd\n");
+
+        // Erasing an empty line echoes nothing.
+        let mut line = Vec::new();
+        assert!(edit_line(&mut line, 0x7f).echo().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn read_line_with_backspace() {
+        console::feed(b"ab\x08c\xc3\xa9\x7fd\rnext");
+        let mut buf = String::from("> ");
+        assert_eq!(stdin().read_line(&mut buf).unwrap(), 4);
+        assert_eq!(buf, "> acd\n");
+        assert_eq!(console::take_output(), b"ab\x08 \x08c\xc3\xa9\x08 \x08d\n");
+
+        // Echo is per handle.
+        let mut quiet = stdin();
+        quiet.set_echo(false);
+        buf.clear();
+        console::feed(b"\n");
+        assert_eq!(quiet.read_line(&mut buf).unwrap(), 5);
+        assert_eq!(buf, "next\n");
+        assert!(console::take_output().is_empty());
+    }
+
+    #[test]
+    fn print_ordered_with_write() {
+        console::take_output();
+        crate::print!("a");
+        stdout().write_all(b"b").unwrap();
+        crate::println!("c{}", 1);
+        write!(stdout().lock(), "d").unwrap();
+        crate::println!();
+        assert_eq!(console::take_output(), b"abc1\nd\n");
+
+        crate::eprint!("e");
+        stderr().write_all(b"f").unwrap();
+        crate::eprintln!("g{}", 2);
+        assert_eq!(console::take_output(), b"efg2\n");
+    }
+
+    #[test]
+    #[cfg(feature = "multitask")]
+    fn locked_lines_do_not_interleave() {
+        use crate::sync::tests::{INIT, SERIAL};
+        use crate::thread;
+
+        let _lock = SERIAL.lock();
+        INIT.call_once(axtask::init_scheduler);
+        console::take_output();
+
+        // Each thread writes its line in pieces, and lets the other one run in
+        // between: the lock keeps the other one out until the line is done.
+        let writers = [b'a', b'b'].map(|c| {
+            thread::spawn(move || {
+                let mut out = stdout().lock();
+                for _ in 0..3 {
+                    out.write_all(&[c]).unwrap();
+                    thread::yield_now();
+                }
+                out.write_all(b"\n").unwrap();
+            })
+        });
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let output = console::take_output();
+        assert!(
+            output == b"aaa\nbbb\n" || output == b"bbb\naaa\n",
+            "{:?}",
+            core::str::from_utf8(&output)
+        );
     }
 }
@@ -21,7 +21,8 @@
 //!     - `alloc-slab`: Use the slab allocator.
 //!     - `alloc-buddy`: Use the buddy system allocator.
 //!     - `paging`: Enable page table manipulation.
-//!     - `tls`: Enable thread-local storage.
+//!     - `tls`: Enable thread-local storage, and the `thread_local!` macro
+//!       with `multitask`.
 //! - Task management
 //!     - `multitask`: Enable multi-threading support.
 //!     - `sched_fifo`: Use the FIFO cooperative scheduler.
@@ -55,7 +56,7 @@ extern crate alloc;
 
 #[cfg(feature = "alloc")]
 #[doc(no_inline)]
-pub use alloc::{boxed, collections, format, string, vec};
+pub use alloc::{boxed, format, string, vec};
 
 #[doc(no_inline)]
 pub use core::{arch, cell, cmp, hint, marker, mem, ops, ptr, slice, str};
@@ -71,6 +72,8 @@ pub mod sync;
 pub mod thread;
 pub mod time;
 
+#[cfg(feature = "alloc")]
+pub mod collections;
 #[cfg(feature = "fs")]
 pub mod fs;
 #[cfg(feature = "net")]
@@ -21,3 +21,32 @@ macro_rules! println {
         $crate::io::__print_impl(format_args!("{}\n", format_args!($($arg)*)));
     }
 }
+
+/// Prints to the standard error.
+///
+/// Equivalent to the [`print!`] macro, except that output goes to
+/// [`io::stderr`] instead.
+///
+/// [`print!`]: crate::print
+/// [`io::stderr`]: crate::io::stderr
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => {
+        $crate::io::__eprint_impl(format_args!($($arg)*));
+    }
+}
+
+/// Prints to the standard error, with a newline.
+///
+/// Equivalent to the [`println!`] macro, except that output goes to
+/// [`io::stderr`] instead.
+///
+/// [`println!`]: crate::println
+/// [`io::stderr`]: crate::io::stderr
+#[macro_export]
+macro_rules! eprintln {
+    () => { $crate::eprint!("\n") };
+    ($($arg:tt)*) => {
+        $crate::io::__eprint_impl(format_args!("{}\n", format_args!($($arg)*)));
+    }
+}
@@ -20,11 +20,31 @@ mod udp;
 
 pub use self::socket_addr::{IpAddr, Ipv4Addr, Ipv6Addr};
 pub use self::socket_addr::{SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
-pub use self::tcp::{TcpListener, TcpStream};
+pub use self::tcp::{Incoming, TcpListener, TcpStream};
 pub use self::udp::UdpSocket;
 
+#[cfg(feature = "dns")]
+#[doc(cfg(feature = "dns"))]
+pub use self::socket_addr::lookup_host;
+
 use crate::io;
 
+/// Possible values which can be passed to the [`TcpStream::shutdown`] method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shutdown {
+    /// The reading portion of the [`TcpStream`] should be shut down.
+    ///
+    /// All currently blocked and future reads will return <code>[Ok]\(0)</code>.
+    Read,
+    /// The writing portion of the [`TcpStream`] should be shut down.
+    ///
+    /// All currently blocked and future writes will return an error.
+    Write,
+    /// Both the reading and the writing portions of the [`TcpStream`] should
+    /// be shut down.
+    Both,
+}
+
 fn each_addr<A: ToSocketAddrs, F, T>(addr: A, mut f: F) -> io::Result<T>
 where
     F: FnMut(io::Result<&SocketAddr>) -> io::Result<T>,
@@ -44,3 +64,26 @@ where
         axerrno::ax_err_type!(InvalidInput, "could not resolve to any addresses")
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Shutdown, TcpStream};
+    use crate::io;
+
+    #[test]
+    fn test_shutdown_signature_matches_std() {
+        // Code written against `std`, e.g. `stream.shutdown(Shutdown::Write)`,
+        // builds against axstd as well.
+        let _: fn(&TcpStream, Shutdown) -> io::Result<()> = TcpStream::shutdown;
+        let _: fn(&std::net::TcpStream, std::net::Shutdown) -> std::io::Result<()> =
+            std::net::TcpStream::shutdown;
+
+        let std_hows = [
+            std::net::Shutdown::Read,
+            std::net::Shutdown::Write,
+            std::net::Shutdown::Both,
+        ];
+        let hows = [Shutdown::Read, Shutdown::Write, Shutdown::Both];
+        assert_eq!(format!("{:?}", hows), format!("{:?}", std_hows));
+    }
+}
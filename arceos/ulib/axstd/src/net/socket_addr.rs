@@ -91,12 +91,9 @@ mod no_dns {
         fn to_socket_addrs(&self) -> io::Result<option::IntoIter<SocketAddr>> {
             let (host, port) = *self;
             Ok(host
-                .parse::<Ipv4Addr>()
+                .parse::<IpAddr>()
                 .ok()
-                .map(|addr| {
-                    let addr = SocketAddrV4::new(addr, port);
-                    SocketAddr::V4(addr)
-                })
+                .map(|addr| SocketAddr::new(addr, port))
                 .into_iter())
         }
     }
@@ -124,21 +121,43 @@ mod no_dns {
     }
 }
 
+#[cfg(feature = "dns")]
+pub use self::dns::lookup_host;
+
 #[cfg(feature = "dns")]
 #[doc(cfg(feature = "net"))]
 mod dns {
     use super::*;
     use alloc::{vec, vec::Vec};
 
+    /// Resolves a host name to all its IP addresses, in the order the DNS
+    /// server returned them, so that callers can fall back to the next one.
+    ///
+    /// A string representation of an [`IpAddr`] resolves to that address
+    /// alone. Only A records are queried, as the network stack supports IPv4
+    /// only.
+    ///
+    /// Returns a [`NotFound`](io::Error::NotFound) error if the name has no
+    /// records.
+    pub fn lookup_host(host: &str) -> io::Result<Vec<IpAddr>> {
+        if let Ok(addr) = host.parse::<IpAddr>() {
+            return Ok(vec![addr]);
+        }
+        let addrs = arceos_api::net::ax_dns_query(host)?;
+        if addrs.is_empty() {
+            return Err(axerrno::ax_err_type!(NotFound, "no address records"));
+        }
+        Ok(addrs)
+    }
+
     impl ToSocketAddrs for (&str, u16) {
         type Iter = vec::IntoIter<SocketAddr>;
         fn to_socket_addrs(&self) -> io::Result<vec::IntoIter<SocketAddr>> {
             let (host, port) = *self;
 
             // try to parse the host as a regular IP address first
-            if let Ok(addr) = host.parse::<Ipv4Addr>() {
-                let addr = SocketAddrV4::new(addr, port);
-                return Ok(vec![SocketAddr::V4(addr)].into_iter());
+            if let Ok(addr) = host.parse::<IpAddr>() {
+                return Ok(vec![SocketAddr::new(addr, port)].into_iter());
             }
 
             Ok(arceos_api::net::ax_dns_query(host)?
@@ -1,14 +1,31 @@
-use super::{SocketAddr, ToSocketAddrs};
+use super::{Shutdown, SocketAddr, ToSocketAddrs};
 use crate::io::{self, prelude::*};
+use crate::time::{Duration, Instant};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use arceos_api::net::{self as api, AxTcpSocketHandle};
+use axerrno::{ax_err, AxError};
 
 /// A TCP stream between a local and a remote socket.
-pub struct TcpStream(AxTcpSocketHandle);
+pub struct TcpStream {
+    socket: AxTcpSocketHandle,
+    read_timeout: Timeout,
+    write_timeout: Timeout,
+    nonblocking: AtomicBool,
+}
 
 /// A TCP socket server, listening for connections.
 pub struct TcpListener(AxTcpSocketHandle);
 
+/// An iterator that infinitely [`accept`]s connections on a [`TcpListener`].
+///
+/// This `struct` is created by the [`TcpListener::incoming`] method.
+///
+/// [`accept`]: TcpListener::accept
+pub struct Incoming<'a> {
+    listener: &'a TcpListener,
+}
+
 impl TcpStream {
     /// Opens a TCP connection to a remote host.
     ///
@@ -25,35 +42,154 @@ impl TcpStream {
             let addr = addr?;
             let socket = api::ax_tcp_socket();
             api::ax_tcp_connect(&socket, *addr)?;
-            Ok(TcpStream(socket))
+            Ok(TcpStream::new(socket))
         })
     }
 
+    fn new(socket: AxTcpSocketHandle) -> Self {
+        Self {
+            socket,
+            read_timeout: Timeout::new(),
+            write_timeout: Timeout::new(),
+            nonblocking: AtomicBool::new(false),
+        }
+    }
+
     /// Returns the socket address of the local half of this TCP connection.
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
-        api::ax_tcp_socket_addr(&self.0)
+        api::ax_tcp_socket_addr(&self.socket)
     }
 
     /// Returns the socket address of the remote peer of this TCP connection.
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
-        api::ax_tcp_peer_addr(&self.0)
+        api::ax_tcp_peer_addr(&self.socket)
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    ///
+    /// After [`Shutdown::Write`], the peer reads EOF once it has received the
+    /// data sent before. After [`Shutdown::Read`], reads return 0.
+    ///
+    /// This is the signature of `std`. It used to take no argument and shut
+    /// down both halves, which is now `shutdown(Shutdown::Both)`.
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        match how {
+            Shutdown::Read => api::ax_tcp_shutdown_read(&self.socket),
+            Shutdown::Write => api::ax_tcp_shutdown_write(&self.socket),
+            Shutdown::Both => api::ax_tcp_shutdown(&self.socket),
+        }
+    }
+
+    /// Sets the read timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then [`read`] calls will block
+    /// indefinitely. Otherwise, a [`read`] that waits longer than `dur`
+    /// returns an error of kind [`WouldBlock`].
+    ///
+    /// An [`Err`] is returned if the zero [`Duration`] is passed to this
+    /// method.
+    ///
+    /// [`read`]: Read::read
+    /// [`WouldBlock`]: io::Error::WouldBlock
+    pub fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.read_timeout.set(dur)
+    }
+
+    /// Sets the write timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then [`write`] calls will block
+    /// indefinitely. Otherwise, a [`write`] that waits longer than `dur`
+    /// returns an error of kind [`WouldBlock`].
+    ///
+    /// An [`Err`] is returned if the zero [`Duration`] is passed to this
+    /// method.
+    ///
+    /// [`write`]: Write::write
+    /// [`WouldBlock`]: io::Error::WouldBlock
+    pub fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.write_timeout.set(dur)
+    }
+
+    /// Returns the read timeout of this socket.
+    pub fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        Ok(self.read_timeout.get())
+    }
+
+    /// Returns the write timeout of this socket.
+    pub fn write_timeout(&self) -> io::Result<Option<Duration>> {
+        Ok(self.write_timeout.get())
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent
+    /// from this socket. It must be in `1..=255`.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        api::ax_tcp_set_ttl(&self.socket, ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        api::ax_tcp_ttl(&self.socket)
     }
 
-    /// Shuts down the connection.
-    pub fn shutdown(&self) -> io::Result<()> {
-        api::ax_tcp_shutdown(&self.0)
+    /// Moves this TCP stream into or out of nonblocking mode.
+    ///
+    /// In nonblocking mode, [`read`] and [`write`] return immediately with an
+    /// error of kind [`WouldBlock`] if the operation cannot be completed yet,
+    /// and the read/write timeouts are not used.
+    ///
+    /// [`read`]: Read::read
+    /// [`write`]: Write::write
+    /// [`WouldBlock`]: io::Error::WouldBlock
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        api::ax_tcp_set_nonblocking(&self.socket, nonblocking)?;
+        self.nonblocking.store(nonblocking, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Calls `f` until it no longer returns [`WouldBlock`] or the `timeout`
+    /// elapses.
+    ///
+    /// The socket is switched to nonblocking mode for the duration of the
+    /// call, so that each attempt returns immediately.
+    ///
+    /// [`WouldBlock`]: io::Error::WouldBlock
+    fn with_timeout<T, F>(&self, timeout: Option<Duration>, mut f: F) -> io::Result<T>
+    where
+        F: FnMut(&AxTcpSocketHandle) -> io::Result<T>,
+    {
+        let timeout = match timeout {
+            Some(timeout) if !self.nonblocking.load(Ordering::Relaxed) => timeout,
+            _ => return f(&self.socket),
+        };
+        let deadline = Instant::now() + timeout;
+        api::ax_tcp_set_nonblocking(&self.socket, true)?;
+        // No early return until the blocking mode is restored.
+        let res = loop {
+            match api::ax_poll_interfaces().and_then(|_| f(&self.socket)) {
+                Err(AxError::WouldBlock) if Instant::now() < deadline => crate::thread::yield_now(),
+                res => break res,
+            }
+        };
+        api::ax_tcp_set_nonblocking(&self.socket, false)?;
+        res
     }
 }
 
 impl Read for TcpStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        api::ax_tcp_recv(&self.0, buf)
+        self.with_timeout(self.read_timeout.get(), |socket| {
+            api::ax_tcp_recv(socket, buf)
+        })
     }
 }
 
 impl Write for TcpStream {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        api::ax_tcp_send(&self.0, buf)
+        self.with_timeout(self.write_timeout.get(), |socket| {
+            api::ax_tcp_send(socket, buf)
+        })
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -100,6 +236,66 @@ impl TcpListener {
     /// is established. When established, the corresponding [`TcpStream`] and the
     /// remote peer's address will be returned.
     pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
-        api::ax_tcp_accept(&self.0).map(|(a, b)| (TcpStream(a), b))
+        api::ax_tcp_accept(&self.0).map(|(a, b)| (TcpStream::new(a), b))
+    }
+
+    /// Moves this TCP listener into or out of nonblocking mode.
+    ///
+    /// In nonblocking mode, [`accept`] returns an error of kind [`WouldBlock`]
+    /// immediately if there is no pending connection.
+    ///
+    /// [`accept`]: TcpListener::accept
+    /// [`WouldBlock`]: io::Error::WouldBlock
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        api::ax_tcp_set_nonblocking(&self.0, nonblocking)
+    }
+
+    /// Returns an iterator over the connections being received on this
+    /// listener.
+    ///
+    /// The returned iterator will never return [`None`] and will also not yield
+    /// the peer's [`SocketAddr`] structure. Iterating over it is equivalent to
+    /// calling [`TcpListener::accept`] in a loop. Errors are yielded per
+    /// connection, so the caller decides whether to keep iterating.
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming { listener: self }
+    }
+}
+
+impl Iterator for Incoming<'_> {
+    type Item = io::Result<TcpStream>;
+
+    fn next(&mut self) -> Option<io::Result<TcpStream>> {
+        Some(self.listener.accept().map(|(stream, _)| stream))
+    }
+}
+
+/// An optional timeout that can be updated through a shared reference.
+///
+/// The timeout is stored in nanoseconds, zero stands for no timeout.
+struct Timeout(AtomicU64);
+
+impl Timeout {
+    const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    fn get(&self) -> Option<Duration> {
+        match self.0.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    fn set(&self, dur: Option<Duration>) -> io::Result<()> {
+        let nanos = match dur {
+            None => 0,
+            Some(dur) if dur.is_zero() => {
+                return ax_err!(InvalidInput, "cannot set a 0 duration timeout")
+            }
+            Some(dur) => dur.as_nanos().min(u64::MAX as u128) as u64,
+        };
+        self.0.store(nanos, Ordering::Relaxed);
+        Ok(())
     }
 }
@@ -35,6 +35,44 @@ impl UdpSocket {
         api::ax_udp_peer_addr(&self.0)
     }
 
+    /// Moves this UDP socket into or out of nonblocking mode.
+    ///
+    /// In nonblocking mode, the send and receive operations return an error
+    /// of kind [`WouldBlock`] immediately instead of blocking.
+    ///
+    /// [`WouldBlock`]: io::Error::WouldBlock
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        api::ax_udp_set_nonblocking(&self.0, nonblocking)
+    }
+
+    /// Sets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// When enabled, this socket is allowed to send packets to a broadcast
+    /// address. Otherwise sending to one fails with [`PermissionDenied`].
+    ///
+    /// [`PermissionDenied`]: io::Error::PermissionDenied
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        api::ax_udp_set_broadcast(&self.0, broadcast)
+    }
+
+    /// Gets the value of the `SO_BROADCAST` option for this socket.
+    pub fn broadcast(&self) -> io::Result<bool> {
+        api::ax_udp_broadcast(&self.0)
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent
+    /// from this socket. It must be in `1..=255`.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        api::ax_udp_set_ttl(&self.0, ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        api::ax_udp_ttl(&self.0)
+    }
+
     /// Receives a single datagram message on the socket. On success, returns
     /// the number of bytes read and the origin.
     pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
@@ -73,7 +111,7 @@ impl UdpSocket {
     /// error would only be detected after the first send. If the OS returns an
     /// error for each of the specified addresses, the error returned from the
     /// last connection attempt (the last address) is returned.
-    pub fn connect(&self, addr: SocketAddr) -> io::Result<()> {
+    pub fn connect<A: ToSocketAddrs>(&self, addr: A) -> io::Result<()> {
         super::each_addr(addr, |addr: io::Result<&SocketAddr>| {
             let addr = addr?;
             api::ax_udp_connect(&self.0, *addr)
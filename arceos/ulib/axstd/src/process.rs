@@ -4,7 +4,29 @@
 //! process-related functions will affect the entire system, such as [`exit`]
 //! will shutdown the whole system.
 
-/// Shutdown the whole system.
-pub fn exit(_exit_code: i32) -> ! {
-    arceos_api::sys::ax_terminate();
+/// Shutdown the whole system with the given exit code.
+///
+/// Only RISC-V QEMU reports the code, through the SBI system reset, which can
+/// only tell a failure from a success: QEMU exits with status 1 for any
+/// non-zero code, and 0 otherwise. The other platforms ignore the code.
+pub fn exit(exit_code: i32) -> ! {
+    arceos_api::sys::ax_terminate_with_code(exit_code);
+}
+
+/// The exit code of the system after [`abort`], as for a process killed by
+/// `SIGABRT`.
+///
+/// It only tells an abort from a normal exit in the code passed to the
+/// platform: RISC-V QEMU still exits with status 1, as for any non-zero code
+/// given to [`exit`].
+pub const ABORT_EXIT_CODE: i32 = 134;
+
+/// Terminates the whole system in an abnormal fashion, with the exit code
+/// [`ABORT_EXIT_CODE`].
+///
+/// Like [`exit`], no destructors are run. The abort is reported on the console
+/// first.
+pub fn abort() -> ! {
+    let _ = arceos_api::stdio::ax_console_write_bytes(b"aborted\n");
+    arceos_api::sys::ax_terminate_with_code(ABORT_EXIT_CODE);
 }
@@ -0,0 +1,91 @@
+//! A reusable thread rendezvous point.
+
+use core::fmt;
+
+use super::{Condvar, Mutex};
+
+struct BarrierState {
+    count: usize,
+    generation_id: usize,
+}
+
+/// A barrier enables multiple threads to synchronize the beginning of some
+/// computation, similar to
+/// [`std::sync::Barrier`](https://doc.rust-lang.org/std/sync/struct.Barrier.html).
+///
+/// The barrier can be reused: once all threads have been released, the next
+/// `n` calls to [`wait`](Barrier::wait) form a new round.
+pub struct Barrier {
+    lock: Mutex<BarrierState>,
+    cvar: Condvar,
+    num_threads: usize,
+}
+
+/// A `BarrierWaitResult` is returned by [`Barrier::wait`] when all threads in
+/// the [`Barrier`] have rendezvoused.
+pub struct BarrierWaitResult(bool);
+
+impl Barrier {
+    /// Creates a new barrier that can block a given number of threads.
+    ///
+    /// A barrier will block `n-1` threads which call [`wait`](Barrier::wait)
+    /// and then wake up all threads at once when the `n`th thread calls
+    /// `wait`.
+    pub const fn new(n: usize) -> Self {
+        Self {
+            lock: Mutex::new(BarrierState {
+                count: 0,
+                generation_id: 0,
+            }),
+            cvar: Condvar::new(),
+            num_threads: n,
+        }
+    }
+
+    /// Blocks the current thread until all threads have rendezvoused here.
+    ///
+    /// A single (arbitrary) thread will receive a [`BarrierWaitResult`] that
+    /// returns `true` from [`is_leader`](BarrierWaitResult::is_leader) when
+    /// returning from this function, and all other threads will receive a
+    /// result that will return `false`.
+    pub fn wait(&self) -> BarrierWaitResult {
+        let mut state = self.lock.lock();
+        let local_gen = state.generation_id;
+        state.count += 1;
+        if state.count < self.num_threads {
+            // Loop on the generation, so spurious wakeups and threads of the
+            // next round don't release us early.
+            let _state = self
+                .cvar
+                .wait_while(state, |state| local_gen == state.generation_id);
+            BarrierWaitResult(false)
+        } else {
+            state.count = 0;
+            state.generation_id = state.generation_id.wrapping_add(1);
+            self.cvar.notify_all();
+            BarrierWaitResult(true)
+        }
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Barrier").finish_non_exhaustive()
+    }
+}
+
+impl BarrierWaitResult {
+    /// Returns `true` if this thread is the "leader thread" for the call to
+    /// [`Barrier::wait`].
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+impl fmt::Debug for BarrierWaitResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BarrierWaitResult")
+            .field("is_leader", &self.is_leader())
+            .finish()
+    }
+}
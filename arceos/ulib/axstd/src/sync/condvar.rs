@@ -0,0 +1,91 @@
+//! A condition variable built on the axtask wait queue.
+
+use core::fmt;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use arceos_api::task::{self as api, AxWaitQueueHandle};
+
+use super::MutexGuard;
+
+/// A Condition Variable, similar to
+/// [`std::sync::Condvar`](https://doc.rust-lang.org/std/sync/struct.Condvar.html).
+///
+/// Condition variables represent the ability to block a thread such that it
+/// consumes no CPU time while waiting for an event to occur. Spurious wakeups
+/// are possible, so callers should always check their predicate in a loop.
+pub struct Condvar {
+    wq: AxWaitQueueHandle,
+    /// Bumped on every notification, so that a waiter can tell whether it has
+    /// been notified since it released the mutex.
+    seq: AtomicU32,
+}
+
+impl Condvar {
+    /// Creates a new condition variable which is ready to be waited on and
+    /// notified.
+    pub const fn new() -> Self {
+        Self {
+            wq: AxWaitQueueHandle::new(),
+            seq: AtomicU32::new(0),
+        }
+    }
+
+    /// Blocks the current thread until this condition variable receives a
+    /// notification.
+    ///
+    /// This function will atomically unlock the mutex specified (represented by
+    /// `guard`) and block the current thread. When this function call returns,
+    /// the lock specified will have been re-acquired.
+    pub fn wait<'a, T: ?Sized>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        let mutex = guard.mutex();
+        // Read the sequence number while still holding the lock: a notifier
+        // has to take the lock to change the predicate, so any notification
+        // after this point bumps `seq` and is not lost.
+        let seq = self.seq.load(Ordering::Acquire);
+        drop(guard);
+        api::ax_wait_queue_wait(&self.wq, || self.seq.load(Ordering::Acquire) != seq, None);
+        mutex.lock()
+    }
+
+    /// Blocks the current thread until `condition` returns `false`.
+    ///
+    /// The `condition` is checked with the lock held, before waiting and after
+    /// every wakeup.
+    pub fn wait_while<'a, T: ?Sized, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        mut condition: F,
+    ) -> MutexGuard<'a, T>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        while condition(&mut *guard) {
+            guard = self.wait(guard);
+        }
+        guard
+    }
+
+    /// Wakes up one blocked thread on this condvar.
+    pub fn notify_one(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        api::ax_wait_queue_wake(&self.wq, 1);
+    }
+
+    /// Wakes up all blocked threads on this condvar.
+    pub fn notify_all(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        api::ax_wait_queue_wake(&self.wq, u32::MAX);
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Condvar").finish_non_exhaustive()
+    }
+}
@@ -7,13 +7,94 @@ pub use core::sync::atomic;
 #[doc(no_inline)]
 pub use alloc::sync::{Arc, Weak};
 
+// Gated like `Shared`: without `multitask` the app has a single thread, even
+// with `smp`, as the other CPUs run no app code then.
+#[cfg(all(feature = "alloc", not(feature = "multitask")))]
+#[doc(no_inline)]
+pub use alloc::rc::Rc;
+
+/// A reference-counted pointer, with non-atomic counts when there are no
+/// other threads.
+///
+/// It is [`Rc`](alloc::rc::Rc) without the `multitask` feature, like the `Rc`
+/// re-export, and [`Arc`] with it, so that it can be moved into spawned
+/// threads. Without `multitask`, it is neither [`Send`] nor [`Sync`], so code
+/// that needs either (e.g. a `static`) should use [`Arc`] directly.
+#[cfg(feature = "alloc")]
+pub type Shared<T> = SharedPtr<T>;
+
+#[cfg(all(feature = "alloc", not(feature = "multitask")))]
+use alloc::rc::Rc as SharedPtr;
+#[cfg(all(feature = "alloc", feature = "multitask"))]
+use alloc::sync::Arc as SharedPtr;
+
+#[cfg(feature = "multitask")]
+mod barrier;
+#[cfg(feature = "multitask")]
+mod condvar;
 #[cfg(feature = "multitask")]
 mod mutex;
+#[cfg(feature = "multitask")]
+mod once;
+#[cfg(feature = "multitask")]
+mod rwlock;
+#[cfg(feature = "multitask")]
+mod wait_queue;
+
+#[cfg(feature = "multitask")]
+#[doc(cfg(feature = "multitask"))]
+pub mod mpsc;
+
+#[cfg(all(test, feature = "multitask"))]
+pub(crate) mod tests;
 
+#[cfg(all(test, feature = "alloc", not(feature = "multitask")))]
+mod rc_tests {
+    use super::{Rc, Shared};
+
+    #[test]
+    fn test_rc_refcount() {
+        let a = Rc::new(5);
+        let weak = Rc::downgrade(&a);
+        let b = Rc::clone(&a);
+        assert_eq!(Rc::strong_count(&a), 2);
+        drop(a);
+        assert_eq!(Rc::strong_count(&b), 1);
+        assert_eq!(weak.upgrade().as_deref(), Some(&5));
+        drop(b);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_shared_is_rc() {
+        let a: Rc<u32> = Shared::new(5);
+        assert_eq!(Rc::strong_count(&a), 1);
+    }
+}
+
+#[cfg(feature = "multitask")]
+#[doc(cfg(feature = "multitask"))]
+pub use self::barrier::{Barrier, BarrierWaitResult};
+#[cfg(feature = "multitask")]
+#[doc(cfg(feature = "multitask"))]
+pub use self::condvar::Condvar;
 #[cfg(feature = "multitask")]
 #[doc(cfg(feature = "multitask"))]
 pub use self::mutex::{Mutex, MutexGuard};
+#[cfg(feature = "multitask")]
+#[doc(cfg(feature = "multitask"))]
+pub use self::once::{Once, OnceCell};
+#[cfg(feature = "multitask")]
+#[doc(cfg(feature = "multitask"))]
+pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(feature = "multitask")]
+#[doc(cfg(feature = "multitask"))]
+pub use self::wait_queue::WaitQueue;
 
 #[cfg(not(feature = "multitask"))]
 #[doc(cfg(not(feature = "multitask")))]
 pub use kspin::{SpinRaw as Mutex, SpinRawGuard as MutexGuard}; // never used in IRQ context
+
+/// A spin lock that disables local IRQs (and preemption) while held, so that
+/// it can guard data shared with interrupt handlers. It never blocks.
+pub use kspin::{SpinNoIrq as SpinNoIrqMutex, SpinNoIrqGuard as SpinNoIrqMutexGuard};
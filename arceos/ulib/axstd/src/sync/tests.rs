@@ -0,0 +1,363 @@
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::sync::{mpsc, Barrier, Condvar, Mutex, Once, OnceCell, RwLock, WaitQueue};
+use crate::thread;
+
+/// Initializes the scheduler, once for all the tests that run threads.
+pub(crate) static INIT: std::sync::Once = std::sync::Once::new();
+/// Held by the tests that run threads, which share the scheduler.
+pub(crate) static SERIAL: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+fn test_condvar() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static READY: Mutex<bool> = Mutex::new(false);
+    static CVAR: Condvar = Condvar::new();
+    static CONSUMED: AtomicBool = AtomicBool::new(false);
+
+    let consumer = thread::spawn(|| {
+        let mut ready = READY.lock();
+        while !*ready {
+            ready = CVAR.wait(ready);
+        }
+        CONSUMED.store(true, Ordering::Relaxed);
+    });
+
+    // Let the consumer block on the condvar first.
+    thread::yield_now();
+    assert!(!CONSUMED.load(Ordering::Relaxed));
+    *READY.lock() = true;
+    CVAR.notify_one();
+    consumer.join().unwrap();
+    assert!(CONSUMED.load(Ordering::Relaxed));
+}
+
+#[test]
+fn test_rwlock() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static LOCK: RwLock<u32> = RwLock::new(0);
+    static READ_DONE: AtomicBool = AtomicBool::new(false);
+
+    // Read guards coexist, and exclude writers.
+    let r1 = LOCK.read();
+    let r2 = LOCK.read();
+    assert!(LOCK.try_read().is_some());
+    assert!(LOCK.try_write().is_none());
+    drop((r1, r2));
+
+    // A write guard excludes readers until it is dropped.
+    let mut w = LOCK.write();
+    assert!(LOCK.try_read().is_none());
+    let reader = thread::spawn(|| {
+        let value = *LOCK.read();
+        READ_DONE.store(true, Ordering::Relaxed);
+        value
+    });
+    thread::yield_now();
+    assert!(!READ_DONE.load(Ordering::Relaxed));
+    *w = 42;
+    drop(w);
+    assert_eq!(reader.join().unwrap(), 42);
+    assert!(READ_DONE.load(Ordering::Relaxed));
+}
+
+#[test]
+fn test_mpsc() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_ITEMS: u32 = 100;
+
+    let (tx, rx) = mpsc::channel();
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Empty));
+    let producer = thread::spawn(move || {
+        for i in 0..NUM_ITEMS {
+            tx.send(i).unwrap();
+            if i % 10 == 0 {
+                thread::yield_now();
+            }
+        }
+    });
+
+    // Receive until all the senders are dropped.
+    let mut received = Vec::new();
+    while let Ok(i) = rx.recv() {
+        received.push(i);
+    }
+    assert!(received.into_iter().eq(0..NUM_ITEMS));
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    producer.join().unwrap();
+}
+
+#[test]
+fn test_once() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_TASKS: usize = 5;
+    static ONCE: Once = Once::new();
+    static CELL: OnceCell<usize> = OnceCell::new();
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+    let handles: Vec<_> = (0..NUM_TASKS)
+        .map(|i| {
+            thread::spawn(move || {
+                ONCE.call_once(|| {
+                    // Give the other threads the chance to contend.
+                    thread::yield_now();
+                    RUNS.fetch_add(1, Ordering::Relaxed);
+                });
+                assert!(ONCE.is_completed());
+                *CELL.get_or_init(|| {
+                    thread::yield_now();
+                    i
+                })
+            })
+        })
+        .collect();
+
+    let values: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(RUNS.load(Ordering::Relaxed), 1);
+    // Every thread sees the value of the one which initialized the cell.
+    assert!(values.iter().all(|v| Some(v) == CELL.get()));
+    assert_eq!(CELL.set(100), Err(100));
+}
+
+#[test]
+fn test_barrier() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_TASKS: usize = 4;
+    const NUM_ROUNDS: usize = 2;
+    static BARRIER: Barrier = Barrier::new(NUM_TASKS);
+    static ARRIVED: [AtomicUsize; NUM_ROUNDS] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+    static LEADERS: [AtomicUsize; NUM_ROUNDS] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+    let handles: Vec<_> = (0..NUM_TASKS)
+        .map(|i| {
+            thread::spawn(move || {
+                for round in 0..NUM_ROUNDS {
+                    // Arrive at different times.
+                    for _ in 0..i {
+                        thread::yield_now();
+                    }
+                    ARRIVED[round].fetch_add(1, Ordering::Relaxed);
+                    if BARRIER.wait().is_leader() {
+                        LEADERS[round].fetch_add(1, Ordering::Relaxed);
+                    }
+                    // Nobody gets past the barrier before everyone arrived.
+                    assert_eq!(ARRIVED[round].load(Ordering::Relaxed), NUM_TASKS);
+                }
+            })
+        })
+        .collect();
+
+    for h in handles {
+        h.join().unwrap();
+    }
+    for leaders in &LEADERS {
+        assert_eq!(leaders.load(Ordering::Relaxed), 1);
+    }
+}
+
+#[test]
+fn test_wait_queue() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static WQ: WaitQueue = WaitQueue::new();
+    static FLAG: AtomicBool = AtomicBool::new(false);
+    static WOKEN: AtomicBool = AtomicBool::new(false);
+
+    let waiter = thread::spawn(|| {
+        WQ.wait_until(|| FLAG.load(Ordering::Acquire));
+        WOKEN.store(true, Ordering::Relaxed);
+    });
+
+    // Notifying before the predicate holds does not release the waiter.
+    thread::yield_now();
+    WQ.notify_all();
+    thread::yield_now();
+    assert!(!WOKEN.load(Ordering::Relaxed));
+
+    FLAG.store(true, Ordering::Release);
+    WQ.notify_one();
+    waiter.join().unwrap();
+    assert!(WOKEN.load(Ordering::Relaxed));
+}
+
+#[test]
+fn test_shared_refcount() {
+    use crate::sync::Shared;
+
+    let a = Shared::new(5);
+    let b = Shared::clone(&a);
+    assert_eq!(Shared::strong_count(&a), 2);
+    assert!(Shared::ptr_eq(&a, &b));
+    drop(b);
+    assert_eq!(Shared::strong_count(&a), 1);
+    assert_eq!(Shared::try_unwrap(a).ok(), Some(5));
+}
+
+#[test]
+fn test_thread_builder() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let handle = thread::Builder::new()
+        .name("builder-test".into())
+        .stack_size(arceos_api::config::TASK_STACK_SIZE * 2)
+        .spawn(|| {
+            // A bigger stack than the default one is usable.
+            let buf = [1u8; arceos_api::config::TASK_STACK_SIZE];
+            let sum: usize = core::hint::black_box(&buf)
+                .iter()
+                .map(|&b| b as usize)
+                .sum();
+            (thread::current().name().map(String::from), sum)
+        })
+        .unwrap();
+    assert_eq!(handle.thread().name(), Some("builder-test"));
+    let (name, sum) = handle.join().unwrap();
+    assert_eq!(name.as_deref(), Some("builder-test"));
+    assert_eq!(sum, arceos_api::config::TASK_STACK_SIZE);
+}
+
+#[test]
+fn test_park_unpark() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static RESUMED: AtomicBool = AtomicBool::new(false);
+
+    let child = thread::spawn(|| {
+        thread::park();
+        RESUMED.store(true, Ordering::Relaxed);
+    });
+
+    // The child stays parked until it is unparked.
+    thread::yield_now();
+    thread::yield_now();
+    assert!(!RESUMED.load(Ordering::Relaxed));
+    child.thread().unpark();
+    child.join().unwrap();
+    assert!(RESUMED.load(Ordering::Relaxed));
+
+    // A token made available before parking is not lost.
+    thread::current().unpark();
+    thread::park();
+}
+
+#[test]
+fn test_mutex_try_lock() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static LOCK: Mutex<u32> = Mutex::new(0);
+    static HELD: AtomicBool = AtomicBool::new(false);
+    static RELEASE: AtomicBool = AtomicBool::new(false);
+
+    let holder = thread::spawn(|| {
+        let mut guard = LOCK.lock();
+        HELD.store(true, Ordering::Release);
+        while !RELEASE.load(Ordering::Acquire) {
+            thread::yield_now();
+        }
+        *guard = 1;
+    });
+
+    while !HELD.load(Ordering::Acquire) {
+        thread::yield_now();
+    }
+    assert!(LOCK.try_lock().is_none());
+    RELEASE.store(true, Ordering::Release);
+    holder.join().unwrap();
+
+    let guard = LOCK.try_lock().expect("the lock is released");
+    assert_eq!(*guard, 1);
+}
+
+#[test]
+#[cfg(feature = "irq")]
+fn test_spin_noirq_mutex() {
+    use crate::sync::SpinNoIrqMutex;
+    use axhal::arch::irqs_enabled;
+
+    static LOCK: SpinNoIrqMutex<u32> = SpinNoIrqMutex::new(0);
+
+    let irqs_before = irqs_enabled();
+    let mut guard = LOCK.lock();
+    // IRQs can only be masked on bare metal, the guard leaves them be in a
+    // host process.
+    if cfg!(target_os = "none") {
+        assert!(!irqs_enabled());
+    }
+    assert!(LOCK.try_lock().is_none());
+    *guard = 1;
+    drop(guard);
+
+    assert_eq!(irqs_enabled(), irqs_before);
+    assert_eq!(*LOCK.try_lock().expect("the lock is released"), 1);
+}
+
+#[test]
+fn test_scoped_threads() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    // Each thread fills its own part of an array on this stack, and none of
+    // them is joined explicitly: the scope waits for them all.
+    let mut values = [0usize; 8];
+    thread::scope(|s| {
+        for (i, chunk) in values.chunks_mut(2).enumerate() {
+            s.spawn(move || {
+                for (j, value) in chunk.iter_mut().enumerate() {
+                    thread::yield_now();
+                    *value = i * 10 + j;
+                }
+            });
+        }
+    });
+    assert_eq!(values, [0, 1, 10, 11, 20, 21, 30, 31]);
+}
+
+#[test]
+#[cfg(feature = "tls")]
+fn test_thread_local() {
+    use core::cell::Cell;
+
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    struct Counter(Cell<usize>);
+    impl Drop for Counter {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(self.0.get(), Ordering::Relaxed);
+        }
+    }
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    crate::thread_local! {
+        static COUNTER: Counter = Counter(Cell::new(0));
+    }
+
+    // Each thread counts on its own value, interleaved with the other.
+    let counters: [_; 2] = core::array::from_fn(|i| {
+        thread::spawn(move || {
+            for _ in 0..=i {
+                COUNTER.with(|c| c.0.set(c.0.get() + 1));
+                thread::yield_now();
+            }
+            COUNTER.with(|c| c.0.get())
+        })
+    });
+    let counts = counters.map(|t| t.join().unwrap());
+    assert_eq!(counts, [1, 2]);
+    // The values are dropped as their threads exit.
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 3);
+}
@@ -0,0 +1,77 @@
+//! A wait queue, to build blocking primitives on.
+
+use core::fmt;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use arceos_api::task::{self as api, AxWaitQueueHandle};
+
+/// A queue of threads blocked until some event happens, built on the axtask
+/// wait queue.
+///
+/// It is the building block of the other blocking primitives: a thread waits
+/// for a condition with [`wait_until`], and the threads that change the
+/// condition call [`notify_one`] or [`notify_all`] afterwards.
+///
+/// [`wait_until`]: WaitQueue::wait_until
+/// [`notify_one`]: WaitQueue::notify_one
+/// [`notify_all`]: WaitQueue::notify_all
+pub struct WaitQueue {
+    wq: AxWaitQueueHandle,
+    /// Bumped on every notification, so that a waiter can tell whether it has
+    /// been notified since it started waiting.
+    seq: AtomicU32,
+}
+
+impl WaitQueue {
+    /// Creates an empty wait queue.
+    pub const fn new() -> Self {
+        Self {
+            wq: AxWaitQueueHandle::new(),
+            seq: AtomicU32::new(0),
+        }
+    }
+
+    /// Blocks the current thread until the queue is notified.
+    ///
+    /// Spurious wakeups are possible, so callers waiting for a condition should
+    /// rather use [`wait_until`](WaitQueue::wait_until).
+    pub fn wait(&self) {
+        let seq = self.seq.load(Ordering::Acquire);
+        api::ax_wait_queue_wait(&self.wq, || self.seq.load(Ordering::Acquire) != seq, None);
+    }
+
+    /// Blocks the current thread until `condition` returns `true`.
+    ///
+    /// The condition is checked first, and again each time the thread is
+    /// woken up. Returns immediately if it is already true.
+    pub fn wait_until<F>(&self, condition: F)
+    where
+        F: Fn() -> bool,
+    {
+        api::ax_wait_queue_wait(&self.wq, condition, None);
+    }
+
+    /// Wakes up one thread blocked on this queue, if any.
+    pub fn notify_one(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        api::ax_wait_queue_wake(&self.wq, 1);
+    }
+
+    /// Wakes up all the threads blocked on this queue.
+    pub fn notify_all(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        api::ax_wait_queue_wake(&self.wq, u32::MAX);
+    }
+}
+
+impl Default for WaitQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for WaitQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitQueue").finish_non_exhaustive()
+    }
+}
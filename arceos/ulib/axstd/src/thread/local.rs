@@ -0,0 +1,136 @@
+//! Thread-local storage, keyed off the current task.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ptr::NonNull;
+
+use kspin::SpinNoIrq;
+
+use super::api;
+
+/// Declares a new thread-local storage key of type [`LocalKey`].
+///
+/// Each thread sees its own instance of the value, initialized with the given
+/// expression on its first access through [`LocalKey::with`].
+///
+/// ```ignore
+/// use std::cell::Cell;
+///
+/// std::thread_local! {
+///     static COUNTER: Cell<u32> = Cell::new(0);
+/// }
+///
+/// COUNTER.with(|c| c.set(c.get() + 1));
+/// ```
+#[macro_export]
+macro_rules! thread_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr; $($rest:tt)*) => {
+        $crate::thread_local!($(#[$attr])* $vis static $name: $t = $init);
+        $crate::thread_local!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr) => {
+        $(#[$attr])* $vis static $name: $crate::thread::LocalKey<$t> = {
+            fn __init() -> $t {
+                $init
+            }
+            $crate::thread::LocalKey::new(__init)
+        };
+    };
+}
+
+/// A thread-local storage key which owns its contents.
+///
+/// Created with the [`thread_local!`] macro. The value of a thread is created
+/// lazily, on its first access, and lives in a map from task IDs to values.
+///
+/// The value is dropped when its thread exits, if the thread was spawned with
+/// [`spawn`](super::spawn) or a [`Builder`](super::Builder). The values of the
+/// main thread and of tasks spawned otherwise are never dropped.
+///
+/// [`thread_local!`]: crate::thread_local
+pub struct LocalKey<T: 'static> {
+    values: SpinNoIrq<BTreeMap<u64, NonNull<T>>>,
+    init: fn() -> T,
+}
+
+// SAFETY: a value is only ever accessed by the thread it was created for.
+unsafe impl<T> Sync for LocalKey<T> {}
+
+impl<T: 'static> LocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> Self {
+        Self {
+            values: SpinNoIrq::new(BTreeMap::new()),
+            init,
+        }
+    }
+
+    /// Acquires a reference to the value of the current thread for this key,
+    /// initializing it first if this thread has not accessed it yet.
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let id = api::ax_current_task_id();
+        let value = self.values.lock().get(&id).copied();
+        let value = value.unwrap_or_else(|| {
+            // Not under the lock, as the initializer may access the key of
+            // another thread-local.
+            let value = NonNull::from(Box::leak(Box::new((self.init)())));
+            self.values.lock().insert(id, value);
+            KEYS_IN_USE.lock().entry(id).or_default().push(self);
+            value
+        });
+        // SAFETY: the value is only freed once this thread exits, and only
+        // this thread accesses it.
+        f(unsafe { value.as_ref() })
+    }
+}
+
+/// The type-erased [`LocalKey`], to drop the value of an exiting thread.
+trait LocalValues: Sync {
+    fn drop_value(&self, id: u64);
+}
+
+impl<T: 'static> LocalValues for LocalKey<T> {
+    fn drop_value(&self, id: u64) {
+        // Not under the lock, as the destructor may access the key of another
+        // thread-local.
+        let value = self.values.lock().remove(&id);
+        if let Some(value) = value {
+            // SAFETY: the value was leaked from a box in `with`, and its thread
+            // is exiting, so nothing refers to it anymore.
+            drop(unsafe { Box::from_raw(value.as_ptr()) });
+        }
+    }
+}
+
+/// The keys that hold a value of each thread.
+static KEYS_IN_USE: SpinNoIrq<BTreeMap<u64, Vec<&'static dyn LocalValues>>> =
+    SpinNoIrq::new(BTreeMap::new());
+
+/// Drops the thread-local values of the thread `id`. Called by the thread
+/// itself, when it exits.
+pub(super) fn drop_values(id: u64) {
+    // Destructors may access thread-locals again, creating new values.
+    loop {
+        let keys = KEYS_IN_USE.lock().remove(&id);
+        let Some(keys) = keys else {
+            break;
+        };
+        for key in keys {
+            key.drop_value(id);
+        }
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
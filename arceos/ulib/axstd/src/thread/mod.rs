@@ -1,10 +1,19 @@
 //! Native threads.
 
+#[cfg(all(feature = "multitask", feature = "tls"))]
+mod local;
 #[cfg(feature = "multitask")]
 mod multi;
 #[cfg(feature = "multitask")]
+mod scoped;
+#[cfg(all(feature = "multitask", feature = "tls"))]
+pub use local::LocalKey;
+#[cfg(feature = "multitask")]
 pub use multi::*;
+#[cfg(feature = "multitask")]
+pub use scoped::{scope, Scope, ScopedJoinHandle};
 
+use crate::time::{Duration, Instant};
 use arceos_api::task as api;
 
 /// Current thread gives up the CPU time voluntarily, and switches to another
@@ -28,14 +37,61 @@ pub fn exit(exit_code: i32) -> ! {
 ///
 /// If one of `multitask` or `irq` features is not enabled, it uses busy-wait
 /// instead.
-pub fn sleep(dur: core::time::Duration) {
-    sleep_until(arceos_api::time::ax_wall_time() + dur);
+pub fn sleep(dur: Duration) {
+    api::ax_sleep_until(arceos_api::time::ax_wall_time() + dur);
 }
 
 /// Current thread is going to sleep, it will be woken up at the given deadline.
 ///
+/// Returns immediately if the deadline has already passed. Sleeping to an
+/// absolute deadline does not accumulate drift in periodic loops, unlike
+/// repeated calls to [`sleep`].
+///
 /// If one of `multitask` or `irq` features is not enabled, it uses busy-wait
 /// instead.
-pub fn sleep_until(deadline: arceos_api::time::AxTimeValue) {
-    api::ax_sleep_until(deadline);
+pub fn sleep_until(deadline: Instant) {
+    if let Some(dur) = time_until(deadline, Instant::now()) {
+        sleep(dur);
+    }
+}
+
+/// Returns how long to sleep from `now` to reach `deadline`, or [`None`] if it
+/// has already passed.
+fn time_until(deadline: Instant, now: Instant) -> Option<Duration> {
+    (deadline > now).then(|| deadline - now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_until_passed_deadline() {
+        // Deadlines which have already passed return without sleeping, so a
+        // periodic loop which fell behind catches up instead of drifting.
+        let start = Instant::now();
+        sleep_until(start);
+        if let Some(past) = start.checked_sub(Duration::from_secs(1)) {
+            sleep_until(past);
+        }
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn periodic_wakeups_do_not_drift() {
+        // Every wakeup happens some time after its deadline, e.g. when other
+        // threads run first. Sleeping until `start + k * period` absorbs it,
+        // instead of adding it up like `sleep(period)` would.
+        let period = Duration::from_millis(10);
+        let late = Duration::from_millis(3);
+        let start = Instant::now();
+        let mut now = start;
+        for k in 1..=2 {
+            let deadline = start + period * k;
+            now = now + time_until(deadline, now).unwrap() + late;
+            assert_eq!(now, deadline + late);
+        }
+        assert_eq!(time_until(now, now), None);
+        assert_eq!(time_until(start, now), None);
+    }
 }
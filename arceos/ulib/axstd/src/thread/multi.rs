@@ -3,19 +3,40 @@
 extern crate alloc;
 
 use crate::io;
-use alloc::{string::String, sync::Arc};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, sync::Arc};
+use core::sync::atomic::{AtomicBool, Ordering};
 use core::{cell::UnsafeCell, num::NonZeroU64};
 
-use arceos_api::task::{self as api, AxTaskHandle};
+use super::scoped::ScopeData;
+
+use arceos_api::task::{self as api, AxTaskHandle, AxWaitQueueHandle};
 use axerrno::ax_err_type;
+use kspin::SpinNoIrq;
+
+/// Handles of the running threads, by task ID, so that [`current`] returns the
+/// same one every time.
+///
+/// Threads spawned by [`Builder`] are removed when they finish. Others (e.g.
+/// the main thread) are added on their first call to [`current`], and stay.
+static THREADS: SpinNoIrq<BTreeMap<u64, Thread>> = SpinNoIrq::new(BTreeMap::new());
 
 /// A unique identifier for a running thread.
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
 pub struct ThreadId(NonZeroU64);
 
 /// A handle to a thread.
+#[derive(Clone)]
 pub struct Thread {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
     id: ThreadId,
+    name: Option<String>,
+    /// Whether the unpark token is available.
+    park_token: AtomicBool,
+    /// Where the thread blocks in [`park`].
+    park_wq: AxWaitQueueHandle,
 }
 
 impl ThreadId {
@@ -27,15 +48,37 @@ impl ThreadId {
 }
 
 impl Thread {
-    fn from_id(id: u64) -> Self {
+    fn new(id: u64, name: String) -> Self {
         Self {
-            id: ThreadId(NonZeroU64::new(id).unwrap()),
+            inner: Arc::new(Inner {
+                id: ThreadId(NonZeroU64::new(id).unwrap()),
+                name: if name.is_empty() { None } else { Some(name) },
+                park_token: AtomicBool::new(false),
+                park_wq: AxWaitQueueHandle::new(),
+            }),
         }
     }
 
     /// Gets the thread's unique identifier.
     pub fn id(&self) -> ThreadId {
-        self.id
+        self.inner.id
+    }
+
+    /// Gets the thread's name.
+    ///
+    /// Returns [`None`] for threads spawned without a name.
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name.as_deref()
+    }
+
+    /// Atomically makes the handle's token available if it is not already.
+    ///
+    /// Every thread is equipped with some basic low-level blocking support, via
+    /// the [`park`] function and the `unpark()` method. If the thread is not
+    /// parked yet, the next call to [`park`] returns immediately.
+    pub fn unpark(&self) {
+        self.inner.park_token.store(true, Ordering::Release);
+        api::ax_wait_queue_wake(&self.inner.park_wq, 1);
     }
 }
 
@@ -86,14 +129,26 @@ impl Builder {
         F: Send + 'static,
         T: Send + 'static,
     {
-        unsafe { self.spawn_unchecked(f) }
+        unsafe { self.spawn_unchecked(f, None) }
     }
 
-    unsafe fn spawn_unchecked<F, T>(self, f: F) -> io::Result<JoinHandle<T>>
+    /// Spawns a thread that may borrow data living for `'a`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must make sure the thread has finished before `'a` ends,
+    /// which [`scope`] does by waiting on `scope_data`.
+    ///
+    /// [`scope`]: super::scope
+    pub(super) unsafe fn spawn_unchecked<'a, F, T>(
+        self,
+        f: F,
+        scope_data: Option<Arc<ScopeData>>,
+    ) -> io::Result<JoinHandle<T>>
     where
         F: FnOnce() -> T,
-        F: Send + 'static,
-        T: Send + 'static,
+        F: Send + 'a,
+        T: Send + 'a,
     {
         let name = self.name.unwrap_or_default();
         let stack_size = self
@@ -107,17 +162,32 @@ impl Builder {
 
         let main = move || {
             let ret = f();
+            #[cfg(feature = "tls")]
+            super::local::drop_values(api::ax_current_task_id());
             // SAFETY: `their_packet` as been built just above and moved by the
             // closure (it is an Arc<...>) and `my_packet` will be stored in the
             // same `JoinHandle` as this closure meaning the mutation will be
             // safe (not modify it and affect a value far away).
             unsafe { *their_packet.result.get() = Some(ret) };
             drop(their_packet);
+            THREADS.lock().remove(&api::ax_current_task_id());
+            if let Some(scope_data) = scope_data {
+                scope_data.decrement_num_running_threads();
+            }
         };
+        let main: Box<dyn FnOnce() + Send + 'a> = Box::new(main);
+        // SAFETY: the caller guarantees that the thread ends within `'a`.
+        let main: Box<dyn FnOnce() + Send + 'static> = unsafe { core::mem::transmute(main) };
 
-        let task = api::ax_spawn(main, name, stack_size);
+        // Register the thread before it can run, so that it finds its handle
+        // in `current()`, and does not finish before it is registered.
+        let mut threads = THREADS.lock();
+        let task = api::ax_spawn(main, name.clone(), stack_size);
+        let thread = Thread::new(task.id(), name);
+        threads.insert(task.id(), thread.clone());
+        drop(threads);
         Ok(JoinHandle {
-            thread: Thread::from_id(task.id()),
+            thread,
             native: task,
             packet: my_packet,
         })
@@ -127,7 +197,26 @@ impl Builder {
 /// Gets a handle to the thread that invokes it.
 pub fn current() -> Thread {
     let id = api::ax_current_task_id();
-    Thread::from_id(id)
+    THREADS
+        .lock()
+        .entry(id)
+        .or_insert_with(|| Thread::new(id, api::ax_current_task_name()))
+        .clone()
+}
+
+/// Blocks unless or until the current thread's token is made available.
+///
+/// A call to `park` does not guarantee that the thread will remain parked
+/// forever, and callers should be prepared for this possibility. The token is
+/// consumed on return, and is made available by [`Thread::unpark`].
+pub fn park() {
+    let thread = current();
+    let inner = &thread.inner;
+    api::ax_wait_queue_wait(
+        &inner.park_wq,
+        || inner.park_token.swap(false, Ordering::Acquire),
+        None,
+    );
 }
 
 /// Spawns a new thread, returning a [`JoinHandle`] for it.
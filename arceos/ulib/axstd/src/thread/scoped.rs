@@ -0,0 +1,152 @@
+//! Scoped threads, which may borrow data from the stack of their spawner.
+
+extern crate alloc;
+
+use crate::io;
+use alloc::sync::Arc;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use arceos_api::task::{self as api, AxWaitQueueHandle};
+
+use super::{Builder, JoinHandle, Thread};
+
+/// A scope to spawn scoped threads in.
+///
+/// See [`scope`] for details.
+pub struct Scope<'scope, 'env: 'scope> {
+    data: Arc<ScopeData>,
+    /// Invariance over `'scope`, so that the scope cannot shrink.
+    scope: PhantomData<&'scope mut &'scope ()>,
+    /// Invariance over `'env`, so that the scope cannot grow.
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+/// The state shared between a scope and its threads.
+pub(super) struct ScopeData {
+    num_running_threads: AtomicUsize,
+    wq: AxWaitQueueHandle,
+}
+
+impl ScopeData {
+    fn increment_num_running_threads(&self) {
+        self.num_running_threads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Called by a scoped thread once it has finished.
+    pub(super) fn decrement_num_running_threads(&self) {
+        if self.num_running_threads.fetch_sub(1, Ordering::Release) == 1 {
+            api::ax_wait_queue_wake(&self.wq, u32::MAX);
+        }
+    }
+}
+
+/// An owned permission to join on a scoped thread (block on its termination).
+///
+/// See [`Scope::spawn`] for details.
+pub struct ScopedJoinHandle<'scope, T> {
+    inner: JoinHandle<T>,
+    _marker: PhantomData<&'scope ()>,
+}
+
+/// Creates a scope for spawning scoped threads.
+///
+/// The function passed to `scope` will be provided a [`Scope`] object,
+/// through which scoped threads can be [spawned][`Scope::spawn`].
+///
+/// Unlike non-scoped threads, scoped threads can borrow non-`'static` data,
+/// as the scope guarantees all threads will be joined at the end of the scope.
+///
+/// All threads spawned within the scope that haven't been manually joined
+/// will be automatically joined before this function returns.
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        data: Arc::new(ScopeData {
+            num_running_threads: AtomicUsize::new(0),
+            wq: AxWaitQueueHandle::new(),
+        }),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+
+    let ret = f(&scope);
+
+    // Wait until all the threads are finished.
+    let data = &scope.data;
+    api::ax_wait_queue_wait(
+        &data.wq,
+        || data.num_running_threads.load(Ordering::Acquire) == 0,
+        None,
+    );
+    ret
+}
+
+impl<'scope> Scope<'scope, '_> {
+    /// Spawns a new thread within a scope, returning a [`ScopedJoinHandle`]
+    /// for it.
+    ///
+    /// Unlike non-scoped threads, threads spawned with this function may
+    /// borrow non-`'static` data from the outside the scope. See [`scope`]
+    /// for details.
+    ///
+    /// If the join handle is dropped, the spawned thread will be implicitly
+    /// joined at the end of the scope.
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        Builder::new()
+            .spawn_scoped(self, f)
+            .expect("failed to spawn thread")
+    }
+}
+
+impl Builder {
+    /// Spawns a new scoped thread using the settings set through this
+    /// `Builder`.
+    ///
+    /// Unlike [`Scope::spawn`], this method yields an [`io::Result`] to capture
+    /// any failure to create the thread at the OS level.
+    pub fn spawn_scoped<'scope, 'env, F, T>(
+        self,
+        scope: &'scope Scope<'scope, 'env>,
+        f: F,
+    ) -> io::Result<ScopedJoinHandle<'scope, T>>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        scope.data.increment_num_running_threads();
+        // SAFETY: `scope` waits for the thread to finish before `'scope` ends.
+        let inner = unsafe { self.spawn_unchecked(f, Some(scope.data.clone())) };
+        match inner {
+            Ok(inner) => Ok(ScopedJoinHandle {
+                inner,
+                _marker: PhantomData,
+            }),
+            Err(e) => {
+                scope.data.decrement_num_running_threads();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<T> ScopedJoinHandle<'_, T> {
+    /// Extracts a handle to the underlying thread.
+    pub fn thread(&self) -> &Thread {
+        self.inner.thread()
+    }
+
+    /// Waits for the associated thread to finish.
+    ///
+    /// This function will return immediately if the associated thread has
+    /// already finished.
+    pub fn join(self) -> io::Result<T> {
+        self.inner.join()
+    }
+}
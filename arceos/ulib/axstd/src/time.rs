@@ -1,19 +1,23 @@
 //! Temporal quantification.
 
 use arceos_api::time::AxTimeValue;
+use core::fmt;
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 
 pub use core::time::Duration;
 
 /// A measurement of a monotonically nondecreasing clock.
 /// Opaque and useful only with [`Duration`].
-#[derive(Clone, Copy)]
+///
+/// It is measured from system boot, so it is not affected by changes of the
+/// wall clock.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct Instant(AxTimeValue);
 
 impl Instant {
     /// Returns an instant corresponding to "now".
     pub fn now() -> Instant {
-        Instant(arceos_api::time::ax_wall_time())
+        Instant(arceos_api::time::ax_monotonic_time())
     }
 
     /// Returns the amount of time elapsed from another instant to this one,
@@ -95,3 +99,169 @@ impl Sub<Instant> for Instant {
         self.duration_since(other)
     }
 }
+
+/// A measurement of the system clock, useful for talking to external entities
+/// like the file system or other processes.
+///
+/// Unlike [`Instant`], it follows the wall clock (the RTC, if available), so it
+/// is not guaranteed to be monotonic.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct SystemTime(AxTimeValue);
+
+/// An anchor in time which can be used to create new [`SystemTime`] instances
+/// or learn about where in time a [`SystemTime`] lies.
+///
+/// It is defined to be "1970-01-01 00:00:00 UTC".
+pub const UNIX_EPOCH: SystemTime = SystemTime(Duration::ZERO);
+
+/// An error returned from [`SystemTime::duration_since`] and
+/// [`SystemTime::elapsed`] when the second time is later than the first one.
+#[derive(Clone, Debug)]
+pub struct SystemTimeError(Duration);
+
+impl SystemTime {
+    /// An anchor in time which can be used to create new [`SystemTime`]
+    /// instances. Same as [`UNIX_EPOCH`].
+    pub const UNIX_EPOCH: SystemTime = UNIX_EPOCH;
+
+    /// Returns the system time corresponding to "now".
+    pub fn now() -> SystemTime {
+        SystemTime(arceos_api::time::ax_wall_time())
+    }
+
+    /// Returns the amount of time elapsed from an earlier point in time.
+    ///
+    /// Returns [`Err`] if `earlier` is later than `self`; the error contains
+    /// how far from `self` the time is.
+    pub fn duration_since(&self, earlier: SystemTime) -> Result<Duration, SystemTimeError> {
+        self.0
+            .checked_sub(earlier.0)
+            .ok_or_else(|| SystemTimeError(earlier.0 - self.0))
+    }
+
+    /// Returns the difference between the clock time when this system time was
+    /// created, and the current clock time.
+    ///
+    /// Fails if the wall clock has been moved back since then.
+    pub fn elapsed(&self) -> Result<Duration, SystemTimeError> {
+        SystemTime::now().duration_since(*self)
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self + duration` if `t` can be
+    /// represented as `SystemTime`, `None` otherwise.
+    pub fn checked_add(&self, duration: Duration) -> Option<SystemTime> {
+        self.0.checked_add(duration).map(SystemTime)
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self - duration` if `t` can be
+    /// represented as `SystemTime` (which means it's not before [`UNIX_EPOCH`]),
+    /// `None` otherwise.
+    pub fn checked_sub(&self, duration: Duration) -> Option<SystemTime> {
+        self.0.checked_sub(duration).map(SystemTime)
+    }
+}
+
+impl Add<Duration> for SystemTime {
+    type Output = SystemTime;
+
+    /// # Panics
+    ///
+    /// This function may panic if the resulting point in time cannot be represented by the
+    /// underlying data structure.
+    fn add(self, dur: Duration) -> SystemTime {
+        self.checked_add(dur)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for SystemTime {
+    fn add_assign(&mut self, other: Duration) {
+        *self = *self + other;
+    }
+}
+
+impl Sub<Duration> for SystemTime {
+    type Output = SystemTime;
+
+    fn sub(self, dur: Duration) -> SystemTime {
+        self.checked_sub(dur)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for SystemTime {
+    fn sub_assign(&mut self, other: Duration) {
+        *self = *self - other;
+    }
+}
+
+impl SystemTimeError {
+    /// Returns the positive duration which represents how far forward the
+    /// second system time was from the first.
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl fmt::Display for SystemTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "second time provided was later than self")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instant_ordering() {
+        let a = Instant(Duration::from_secs(1));
+        let mut b = a + Duration::from_millis(500);
+        assert!(a < b);
+        assert_eq!(b - a, Duration::from_millis(500));
+        assert_eq!(b.duration_since(a), Duration::from_millis(500));
+
+        // Saturates instead of panicking.
+        assert_eq!(a - b, Duration::ZERO);
+        assert_eq!(a.duration_since(b), Duration::ZERO);
+
+        b -= Duration::from_millis(500);
+        assert_eq!(a, b);
+        b += Duration::from_secs(1);
+        assert_eq!(b - Duration::from_secs(1), a);
+        assert_eq!(a.checked_sub(Duration::from_secs(2)), None);
+        assert_eq!(a.checked_add(Duration::MAX), None);
+    }
+
+    #[test]
+    fn instant_now_is_monotonic() {
+        let a = Instant::now();
+        let b = Instant::now();
+        assert!(b >= a);
+        assert!(a.elapsed() >= b - a);
+    }
+
+    #[test]
+    fn system_time_since_epoch() {
+        let t = UNIX_EPOCH + Duration::from_secs(10);
+        assert_eq!(SystemTime::UNIX_EPOCH, UNIX_EPOCH);
+        assert_eq!(
+            t.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_secs(10)
+        );
+        assert_eq!(t.duration_since(t).unwrap(), Duration::ZERO);
+
+        // Before the earlier time: the error tells how far.
+        let err = UNIX_EPOCH.duration_since(t).unwrap_err();
+        assert_eq!(err.duration(), Duration::from_secs(10));
+        assert_eq!(err.to_string(), "second time provided was later than self");
+
+        let mut u = t;
+        u -= Duration::from_secs(4);
+        assert_eq!(t.duration_since(u).unwrap(), Duration::from_secs(4));
+        u += Duration::from_secs(4);
+        assert_eq!(u, t);
+        assert_eq!(UNIX_EPOCH.checked_sub(Duration::from_nanos(1)), None);
+        assert_eq!(t.checked_add(Duration::MAX), None);
+    }
+}